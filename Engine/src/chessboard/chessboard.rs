@@ -3,10 +3,11 @@ use crate::constants::BOARD_SIZE;
 use crate::exceptions::{MoveError, PieceError};
 use crate::chessboard::piece::{
     check_pawn_move_blocked, is_a_castling_move, is_big_castling, is_bishop_move_valid,
-    is_king_move_blocked, is_king_move_valid, is_knight_move_valid, is_pawn_move_valid,
-    is_queen_move_valid, is_rook_move_valid, is_small_castling, king_does_castling_correctly,
-    king_is_in_check, pawn_does_en_passant_correctly, pawn_does_not_capture, pawn_moves_diagonally,
-    pawn_promotes, pawn_promotes_correctly, PieceType,
+    is_king_attacked, is_king_move_blocked, is_king_move_valid, is_knight_move_valid,
+    is_pawn_move_valid, is_queen_move_valid, is_rook_move_valid, is_small_castling,
+    king_does_castling_correctly, king_is_in_check, pawn_does_en_passant_correctly,
+    pawn_does_not_capture, pawn_moves_diagonally, pawn_promotes, pawn_promotes_correctly,
+    PieceType,
 };
 use crate::chessboard::player::{Player, PlayerColor};
 
@@ -28,6 +29,11 @@ use crate::chessboard::player::{Player, PlayerColor};
 pub struct Chessboard {
     pub white: Player,
     pub black: Player,
+    pub side_to_move: PlayerColor,
+    // square skipped by the last double pawn push
+    pub en_passant: Option<u64>,
+    pub halfmove_clock: u64,
+    pub fullmove_number: u64,
 }
 
 impl Chessboard {
@@ -35,7 +41,14 @@ impl Chessboard {
         let white = Player::new(PlayerColor::White);
         let black = Player::new(PlayerColor::Black);
 
-        Chessboard { white, black }
+        Chessboard {
+            white,
+            black,
+            side_to_move: PlayerColor::White,
+            en_passant: None,
+            halfmove_clock: 0,
+            fullmove_number: 1,
+        }
     }
 
     pub fn get_board(&self) -> u64 {
@@ -43,15 +56,11 @@ impl Chessboard {
     }
     
     pub fn get_piece_type_color(&self, color: PlayerColor, index: u64)  -> PieceType {
-
-        let mut cloned_state = self.clone();
-        let piece_type = match color {
-            PlayerColor::White => cloned_state.white.get_piece_type(index),
-            PlayerColor::Black => cloned_state.black.get_piece_type(index),
+        match color {
+            PlayerColor::White => self.white.get_piece_type(index),
+            PlayerColor::Black => self.black.get_piece_type(index),
         }
-        .unwrap_or(PieceType::None);
-    
-        piece_type
+        .unwrap_or(PieceType::None)
     }
 
     pub fn get_white_board(&self) -> u64 {
@@ -62,10 +71,21 @@ impl Chessboard {
         self.black.pieces.get_board()
     }
 
+    pub fn is_in_check(&self, color: PlayerColor) -> bool {
+        is_king_attacked(self, color)
+    }
+
     pub fn is_finished(&self) -> bool {
         self.black.king.get_num_squares() == 0 || self.white.king.get_num_squares() == 0
     }
 
+    // passes the turn without moving a piece, for null-move pruning
+    pub fn make_null_move(&mut self) {
+        self.en_passant = None;
+        self.halfmove_clock += 1;
+        self.side_to_move = self.side_to_move.opposite();
+    }
+
     pub fn perform_move(
         &mut self,
         from: &str,
@@ -75,6 +95,46 @@ impl Chessboard {
         let from = Chessboard::convert_square_to_index(from);
         let to = Chessboard::convert_square_to_index(to);
 
+        let is_pawn_move = self.get_piece_type_color(color, from) == PieceType::Pawn;
+        let is_capture = match color {
+            PlayerColor::White => self.black.has_piece_on(to),
+            PlayerColor::Black => self.white.has_piece_on(to),
+        } || (is_pawn_move && from % BOARD_SIZE != to % BOARD_SIZE);
+
+        self.move_pieces(from, to, color)?;
+        self.update_game_state(from, to, color, is_pawn_move, is_capture);
+
+        Ok(())
+    }
+
+    fn update_game_state(
+        &mut self,
+        from: u64,
+        to: u64,
+        color: PlayerColor,
+        is_pawn_move: bool,
+        is_capture: bool,
+    ) {
+        self.en_passant = if is_pawn_move && from.abs_diff(to) == 2 * BOARD_SIZE {
+            Some((from + to) / 2)
+        } else {
+            None
+        };
+
+        if is_pawn_move || is_capture {
+            self.halfmove_clock = 0;
+        } else {
+            self.halfmove_clock += 1;
+        }
+
+        if color == PlayerColor::Black {
+            self.fullmove_number += 1;
+        }
+
+        self.side_to_move = color.opposite();
+    }
+
+    fn move_pieces(&mut self, from: u64, to: u64, color: PlayerColor) -> Result<(), MoveError> {
         // Validation steps:
         // 1. Check if the piece is on the board
         // 2. Check if the piece is the correct color
@@ -120,7 +180,8 @@ impl Chessboard {
             if pawn_moves_diagonally(from, to)
                 && pawn_does_not_capture(to, self.white.clone(), self.black.clone())
             {
-                return if pawn_does_en_passant_correctly(
+                // only onto the square the last move's double push skipped
+                return if self.en_passant == Some(to) && pawn_does_en_passant_correctly(
                     from,
                     to,
                     color,
@@ -248,10 +309,11 @@ impl Chessboard {
         // 4 - check is king is in check
         match king_is_in_check(self.clone(), from, to, color) {
             Err(_) => return Err(MoveError::InvalidMove),
-            Ok(r) => match r {
-                true => return Err(MoveError::InvalidMove),
-                _ => (),
-            },
+            Ok(r) => {
+                if r {
+                    return Err(MoveError::InvalidMove);
+                }
+            }
         }
 
         let move_result = match color {
@@ -381,6 +443,21 @@ impl Chessboard {
         file + rank * BOARD_SIZE
     }
 
+    pub fn parse_square(square: &str) -> Option<u64> {
+        let bytes = square.as_bytes();
+        if bytes.len() != 2 {
+            return None;
+        }
+
+        let file = bytes[0].wrapping_sub(b'a') as u64;
+        let rank = bytes[1].wrapping_sub(b'1') as u64;
+        if file >= BOARD_SIZE || rank >= BOARD_SIZE {
+            return None;
+        }
+
+        Some(file + rank * BOARD_SIZE)
+    }
+
     pub fn convert_index_to_square(index: u64) -> String {
         let file = index % BOARD_SIZE;
         let rank = index / BOARD_SIZE;
@@ -389,6 +466,25 @@ impl Chessboard {
         format!("{}{}", file, rank)
     }
 
+    // coordinate notation, e.g. (12, 28) <-> "e2e4"
+    pub fn convert_move_to_string(next_move: (u64, u64)) -> String {
+        format!(
+            "{}{}",
+            Chessboard::convert_index_to_square(next_move.0),
+            Chessboard::convert_index_to_square(next_move.1)
+        )
+    }
+
+    pub fn parse_move_string(notation: &str) -> Option<(u64, u64)> {
+        if notation.len() != 4 || !notation.is_ascii() {
+            return None;
+        }
+
+        let from = Chessboard::parse_square(&notation[0..2])?;
+        let to = Chessboard::parse_square(&notation[2..4])?;
+        Some((from, to))
+    }
+
     pub fn print_board(&mut self) {
         print!("  ");
         for i in 0..BOARD_SIZE {
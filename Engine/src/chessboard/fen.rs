@@ -0,0 +1,253 @@
+use crate::chessboard::chessboard::Chessboard;
+use crate::chessboard::piece::PieceType;
+use crate::chessboard::player::{Player, PlayerColor};
+use crate::constants::BOARD_SIZE;
+use crate::exceptions::FenError;
+
+pub const STARTING_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+impl Chessboard {
+    // Accepts the four mandatory fields on their own (as used by EPD),
+    // in which case the clocks default to "0 1".
+    pub fn from_fen(fen: &str) -> Result<Chessboard, FenError> {
+        let fields: Vec<&str> = fen.split_whitespace().collect();
+        if fields.len() != 4 && fields.len() != 6 {
+            return Err(FenError::WrongFieldCount);
+        }
+
+        let mut white = Player::empty(PlayerColor::White);
+        let mut black = Player::empty(PlayerColor::Black);
+        parse_piece_placement(fields[0], &mut white, &mut black)?;
+
+        let side_to_move = match fields[1] {
+            "w" => PlayerColor::White,
+            "b" => PlayerColor::Black,
+            _ => return Err(FenError::InvalidSideToMove),
+        };
+
+        parse_castling(fields[2], &mut white, &mut black)?;
+
+        let en_passant = match fields[3] {
+            "-" => None,
+            square => match Chessboard::parse_square(square) {
+                Some(index) if index / BOARD_SIZE == 2 || index / BOARD_SIZE == 5 => Some(index),
+                _ => return Err(FenError::InvalidEnPassant),
+            },
+        };
+
+        let (halfmove_clock, fullmove_number) = if fields.len() == 6 {
+            let halfmove_clock = fields[4].parse().map_err(|_| FenError::InvalidClock)?;
+            let fullmove_number = fields[5].parse().map_err(|_| FenError::InvalidClock)?;
+            (halfmove_clock, fullmove_number)
+        } else {
+            (0, 1)
+        };
+
+        Ok(Chessboard {
+            white,
+            black,
+            side_to_move,
+            en_passant,
+            halfmove_clock,
+            fullmove_number,
+        })
+    }
+
+    pub fn to_fen(&self) -> String {
+        format!(
+            "{} {} {}",
+            self.fen_position(),
+            self.halfmove_clock,
+            self.fullmove_number
+        )
+    }
+
+    // FEN without the clocks: placement, side to move, castling and en passant
+    pub fn fen_position(&self) -> String {
+        let mut placement = String::new();
+
+        for rank in (0..BOARD_SIZE).rev() {
+            let mut empty = 0;
+
+            for file in 0..BOARD_SIZE {
+                let index = rank * BOARD_SIZE + file;
+                let piece = match self.get_piece_type_color(PlayerColor::White, index) {
+                    PieceType::None => (
+                        self.get_piece_type_color(PlayerColor::Black, index),
+                        PlayerColor::Black,
+                    ),
+                    piece_type => (piece_type, PlayerColor::White),
+                };
+
+                if piece.0 == PieceType::None {
+                    empty += 1;
+                    continue;
+                }
+
+                if empty > 0 {
+                    placement.push_str(&empty.to_string());
+                    empty = 0;
+                }
+                placement.push(piece_to_char(piece.0, piece.1));
+            }
+
+            if empty > 0 {
+                placement.push_str(&empty.to_string());
+            }
+            if rank > 0 {
+                placement.push('/');
+            }
+        }
+
+        let side_to_move = match self.side_to_move {
+            PlayerColor::White => "w",
+            PlayerColor::Black => "b",
+        };
+
+        let en_passant = match self.en_passant {
+            Some(index) => Chessboard::convert_index_to_square(index),
+            None => "-".to_string(),
+        };
+
+        format!(
+            "{} {} {} {}",
+            placement,
+            side_to_move,
+            self.castling_string(),
+            en_passant
+        )
+    }
+
+    fn castling_string(&self) -> String {
+        let mut castling = String::new();
+
+        if self.white.can_castle_kingside() && self.white.rooks.get_square(7) {
+            castling.push('K');
+        }
+        if self.white.can_castle_queenside() && self.white.rooks.get_square(0) {
+            castling.push('Q');
+        }
+        if self.black.can_castle_kingside() && self.black.rooks.get_square(63) {
+            castling.push('k');
+        }
+        if self.black.can_castle_queenside() && self.black.rooks.get_square(56) {
+            castling.push('q');
+        }
+
+        if castling.is_empty() {
+            castling.push('-');
+        }
+
+        castling
+    }
+}
+
+pub fn piece_to_char(piece_type: PieceType, color: PlayerColor) -> char {
+    let c = match piece_type {
+        PieceType::Pawn => 'p',
+        PieceType::Knight => 'n',
+        PieceType::Bishop => 'b',
+        PieceType::Rook => 'r',
+        PieceType::Queen => 'q',
+        PieceType::King => 'k',
+        PieceType::None => '.',
+    };
+
+    match color {
+        PlayerColor::White => c.to_ascii_uppercase(),
+        PlayerColor::Black => c,
+    }
+}
+
+pub fn char_to_piece(c: char) -> Option<(PieceType, PlayerColor)> {
+    let piece_type = match c.to_ascii_lowercase() {
+        'p' => PieceType::Pawn,
+        'n' => PieceType::Knight,
+        'b' => PieceType::Bishop,
+        'r' => PieceType::Rook,
+        'q' => PieceType::Queen,
+        'k' => PieceType::King,
+        _ => return None,
+    };
+
+    let color = if c.is_ascii_uppercase() {
+        PlayerColor::White
+    } else {
+        PlayerColor::Black
+    };
+
+    Some((piece_type, color))
+}
+
+fn parse_piece_placement(
+    placement: &str,
+    white: &mut Player,
+    black: &mut Player,
+) -> Result<(), FenError> {
+    let ranks: Vec<&str> = placement.split('/').collect();
+    if ranks.len() != BOARD_SIZE as usize {
+        return Err(FenError::InvalidPiecePlacement);
+    }
+
+    for (i, rank_str) in ranks.iter().enumerate() {
+        let rank = BOARD_SIZE - 1 - i as u64;
+        let mut file = 0;
+        let mut after_digit = false;
+
+        for c in rank_str.chars() {
+            if let Some(skip) = c.to_digit(10) {
+                // a run of empty squares is a single digit from 1 to 8
+                if after_digit || !(1..=BOARD_SIZE).contains(&(skip as u64)) {
+                    return Err(FenError::InvalidPiecePlacement);
+                }
+                file += skip as u64;
+                after_digit = true;
+                continue;
+            }
+            after_digit = false;
+
+            if file >= BOARD_SIZE {
+                return Err(FenError::InvalidPiecePlacement);
+            }
+
+            let (piece_type, color) = char_to_piece(c).ok_or(FenError::InvalidPiecePlacement)?;
+            let player = match color {
+                PlayerColor::White => &mut *white,
+                PlayerColor::Black => &mut *black,
+            };
+            player
+                .add_piece(rank * BOARD_SIZE + file, piece_type)
+                .map_err(|_| FenError::InvalidPiecePlacement)?;
+            file += 1;
+        }
+
+        if file != BOARD_SIZE {
+            return Err(FenError::InvalidPiecePlacement);
+        }
+    }
+
+    if white.king.get_num_squares() != 1 || black.king.get_num_squares() != 1 {
+        return Err(FenError::InvalidPiecePlacement);
+    }
+
+    Ok(())
+}
+
+fn parse_castling(castling: &str, white: &mut Player, black: &mut Player) -> Result<(), FenError> {
+    if castling != "-" {
+        for c in castling.chars() {
+            match c {
+                'K' => white.has_right_rook_moved = false,
+                'Q' => white.has_left_rook_moved = false,
+                'k' => black.has_right_rook_moved = false,
+                'q' => black.has_left_rook_moved = false,
+                _ => return Err(FenError::InvalidCastling),
+            }
+        }
+    }
+
+    white.has_king_moved = white.has_left_rook_moved && white.has_right_rook_moved;
+    black.has_king_moved = black.has_left_rook_moved && black.has_right_rook_moved;
+
+    Ok(())
+}
@@ -0,0 +1,68 @@
+use crate::chessboard::chessboard::Chessboard;
+use crate::chessboard::player::PlayerColor;
+use crate::exceptions::MoveError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GameResult {
+    #[cfg_attr(feature = "serde", serde(rename = "1-0"))]
+    WhiteWins,
+    #[cfg_attr(feature = "serde", serde(rename = "0-1"))]
+    BlackWins,
+    #[cfg_attr(feature = "serde", serde(rename = "1/2-1/2"))]
+    Draw,
+}
+
+impl Chessboard {
+    pub fn get_result(&self) -> Option<GameResult> {
+        if self.white.king.get_num_squares() == 0 {
+            return Some(GameResult::BlackWins);
+        }
+
+        if self.black.king.get_num_squares() == 0 {
+            return Some(GameResult::WhiteWins);
+        }
+
+        None
+    }
+}
+
+// A game as a starting position plus the (from, to) moves played from it
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GameRecord {
+    pub start: Chessboard,
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "crate::chessboard::serialization::move_list")
+    )]
+    pub moves: Vec<(u64, u64)>,
+    pub result: Option<GameResult>,
+}
+
+impl GameRecord {
+    pub fn new(start: Chessboard) -> GameRecord {
+        GameRecord {
+            start,
+            moves: vec![],
+            result: None,
+        }
+    }
+
+    pub fn push_move(&mut self, next_move: (u64, u64)) {
+        self.moves.push(next_move);
+    }
+
+    pub fn replay(&self) -> Result<Chessboard, MoveError> {
+        let mut chessboard = self.start.clone();
+
+        for next_move in &self.moves {
+            let color: PlayerColor = chessboard.side_to_move;
+            let from = Chessboard::convert_index_to_square(next_move.0);
+            let to = Chessboard::convert_index_to_square(next_move.1);
+            chessboard.perform_move(&from, &to, color)?;
+        }
+
+        Ok(chessboard)
+    }
+}
@@ -1,4 +1,11 @@
 pub(crate) mod chessboard;
 mod bitboard;
 pub(crate) mod piece;
-pub(crate) mod player;
\ No newline at end of file
+pub(crate) mod player;
+pub(crate) mod fen;
+pub(crate) mod game;
+pub(crate) mod packed;
+pub(crate) mod zobrist;
+#[cfg(feature = "serde")]
+pub(crate) mod serialization;
+pub(crate) mod see;
\ No newline at end of file
@@ -0,0 +1,195 @@
+use crate::chessboard::chessboard::Chessboard;
+use crate::chessboard::piece::PieceType;
+use crate::chessboard::player::{Player, PlayerColor};
+use crate::exceptions::PackedPositionError;
+
+// Layout (30 bytes, little endian):
+//  0..8   occupancy bitboard
+//  8..24  one nibble per occupied square, in square order (low nibble first)
+//  24     flags: bit 0 = black to move, bits 1..6 = castling history
+//  25     en passant square, NO_EN_PASSANT if none
+//  26..28 halfmove clock
+//  28..30 fullmove number
+pub const PACKED_POSITION_SIZE: usize = 30;
+const MAX_PIECES: u32 = 32;
+const NO_EN_PASSANT: u8 = 0xFF;
+
+const OCCUPANCY_OFFSET: usize = 0;
+const PIECES_OFFSET: usize = 8;
+const FLAGS_OFFSET: usize = 24;
+const EN_PASSANT_OFFSET: usize = 25;
+const HALFMOVE_OFFSET: usize = 26;
+const FULLMOVE_OFFSET: usize = 28;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PackedPosition {
+    pub bytes: [u8; PACKED_POSITION_SIZE],
+}
+
+impl PackedPosition {
+    pub fn from_bytes(bytes: [u8; PACKED_POSITION_SIZE]) -> PackedPosition {
+        PackedPosition { bytes }
+    }
+
+    pub fn as_bytes(&self) -> &[u8; PACKED_POSITION_SIZE] {
+        &self.bytes
+    }
+}
+
+impl Chessboard {
+    pub fn to_packed(&self) -> Result<PackedPosition, PackedPositionError> {
+        let occupancy = self.get_board();
+        if occupancy.count_ones() > MAX_PIECES {
+            return Err(PackedPositionError::TooManyPieces);
+        }
+
+        let mut bytes = [0u8; PACKED_POSITION_SIZE];
+        bytes[OCCUPANCY_OFFSET..OCCUPANCY_OFFSET + 8].copy_from_slice(&occupancy.to_le_bytes());
+
+        let mut remaining = occupancy;
+        let mut nibble = 0;
+        while remaining != 0 {
+            let square = remaining.trailing_zeros() as u64;
+            remaining &= remaining - 1;
+
+            let code = match self.get_piece_type_color(PlayerColor::White, square) {
+                PieceType::None => {
+                    6 + piece_code(self.get_piece_type_color(PlayerColor::Black, square))
+                }
+                piece_type => piece_code(piece_type),
+            };
+
+            bytes[PIECES_OFFSET + nibble / 2] |= code << (4 * (nibble % 2));
+            nibble += 1;
+        }
+
+        let mut flags = 0u8;
+        if self.side_to_move == PlayerColor::Black {
+            flags |= 1;
+        }
+        flags |= castling_bits(&self.white) << 1;
+        flags |= castling_bits(&self.black) << 4;
+        bytes[FLAGS_OFFSET] = flags;
+
+        bytes[EN_PASSANT_OFFSET] = match self.en_passant {
+            Some(square) => square as u8,
+            None => NO_EN_PASSANT,
+        };
+
+        let halfmove_clock: u16 = self
+            .halfmove_clock
+            .try_into()
+            .map_err(|_| PackedPositionError::ClockOverflow)?;
+        let fullmove_number: u16 = self
+            .fullmove_number
+            .try_into()
+            .map_err(|_| PackedPositionError::ClockOverflow)?;
+        bytes[HALFMOVE_OFFSET..HALFMOVE_OFFSET + 2].copy_from_slice(&halfmove_clock.to_le_bytes());
+        bytes[FULLMOVE_OFFSET..FULLMOVE_OFFSET + 2].copy_from_slice(&fullmove_number.to_le_bytes());
+
+        Ok(PackedPosition { bytes })
+    }
+
+    pub fn from_packed(packed: &PackedPosition) -> Result<Chessboard, PackedPositionError> {
+        let bytes = &packed.bytes;
+
+        let mut occupancy_bytes = [0u8; 8];
+        occupancy_bytes.copy_from_slice(&bytes[OCCUPANCY_OFFSET..OCCUPANCY_OFFSET + 8]);
+        let occupancy = u64::from_le_bytes(occupancy_bytes);
+        if occupancy.count_ones() > MAX_PIECES {
+            return Err(PackedPositionError::TooManyPieces);
+        }
+
+        let mut white = Player::empty(PlayerColor::White);
+        let mut black = Player::empty(PlayerColor::Black);
+
+        let mut remaining = occupancy;
+        let mut nibble = 0;
+        while remaining != 0 {
+            let square = remaining.trailing_zeros() as u64;
+            remaining &= remaining - 1;
+
+            let code = (bytes[PIECES_OFFSET + nibble / 2] >> (4 * (nibble % 2))) & 0xF;
+            let (player, code) = match code {
+                0..=5 => (&mut white, code),
+                6..=11 => (&mut black, code - 6),
+                _ => return Err(PackedPositionError::InvalidPieceCode),
+            };
+            player
+                .add_piece(square, piece_from_code(code))
+                .map_err(|_| PackedPositionError::InvalidPieceCode)?;
+            nibble += 1;
+        }
+
+        if white.king.get_num_squares() != 1 || black.king.get_num_squares() != 1 {
+            return Err(PackedPositionError::InvalidKingCount);
+        }
+
+        let flags = bytes[FLAGS_OFFSET];
+        set_castling_bits(&mut white, (flags >> 1) & 0b111);
+        set_castling_bits(&mut black, (flags >> 4) & 0b111);
+
+        let side_to_move = if flags & 1 != 0 {
+            PlayerColor::Black
+        } else {
+            PlayerColor::White
+        };
+
+        let en_passant = match bytes[EN_PASSANT_OFFSET] {
+            NO_EN_PASSANT => None,
+            square if square < 64 => Some(square as u64),
+            _ => return Err(PackedPositionError::InvalidEnPassant),
+        };
+
+        let halfmove_clock =
+            u16::from_le_bytes([bytes[HALFMOVE_OFFSET], bytes[HALFMOVE_OFFSET + 1]]);
+        let fullmove_number =
+            u16::from_le_bytes([bytes[FULLMOVE_OFFSET], bytes[FULLMOVE_OFFSET + 1]]);
+
+        Ok(Chessboard {
+            white,
+            black,
+            side_to_move,
+            en_passant,
+            halfmove_clock: halfmove_clock as u64,
+            fullmove_number: fullmove_number as u64,
+        })
+    }
+}
+
+fn piece_code(piece_type: PieceType) -> u8 {
+    match piece_type {
+        PieceType::Pawn => 0,
+        PieceType::Knight => 1,
+        PieceType::Bishop => 2,
+        PieceType::Rook => 3,
+        PieceType::Queen => 4,
+        PieceType::King => 5,
+        PieceType::None => 0xF,
+    }
+}
+
+fn piece_from_code(code: u8) -> PieceType {
+    match code {
+        0 => PieceType::Pawn,
+        1 => PieceType::Knight,
+        2 => PieceType::Bishop,
+        3 => PieceType::Rook,
+        4 => PieceType::Queen,
+        5 => PieceType::King,
+        _ => PieceType::None,
+    }
+}
+
+fn castling_bits(player: &Player) -> u8 {
+    (player.has_king_moved as u8)
+        | (player.has_left_rook_moved as u8) << 1
+        | (player.has_right_rook_moved as u8) << 2
+}
+
+fn set_castling_bits(player: &mut Player, bits: u8) {
+    player.has_king_moved = bits & 1 != 0;
+    player.has_left_rook_moved = bits & 2 != 0;
+    player.has_right_rook_moved = bits & 4 != 0;
+}
@@ -4,7 +4,8 @@ use crate::constants::{BOARD_SIZE, NUM_SQUARES};
 use crate::exceptions::{MoveError, PieceError};
 use crate::chessboard::player::{Player, PlayerColor};
 
-#[derive(PartialEq, Debug)]
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum PieceType {
     Pawn,
     Knight,
@@ -16,12 +17,7 @@ pub enum PieceType {
 }
 
 pub fn is_pawn_move_valid(from: u64, to: u64, color: PlayerColor) -> Result<(), PieceError> {
-    let result = basic_position_check(from, to);
-    if result.is_err() {
-        return Err(result.unwrap_err());
-    }
-
-    let (from_rank, rank_diff, file_diff) = result.unwrap();
+    let (from_rank, rank_diff, file_diff) = basic_position_check(from, to)?;
 
     match color {
         PlayerColor::White => is_pawn_move_valid_for_white(from_rank, rank_diff, file_diff),
@@ -78,21 +74,10 @@ fn is_pawn_move_valid_for_black(
 }
 
 pub fn is_knight_move_valid(from: u64, to: u64) -> Result<(), PieceError> {
-    let result = basic_position_check(from, to);
-    if result.is_err() {
-        return Err(result.unwrap_err());
-    }
-
-    let (_, rank_diff, file_diff) = result.unwrap();
+    let (_, rank_diff, file_diff) = basic_position_check(from, to)?;
 
-    if (rank_diff == 2 && file_diff == 1)
-        || (rank_diff == 2 && file_diff == -1)
-        || (rank_diff == -2 && file_diff == 1)
-        || (rank_diff == -2 && file_diff == -1)
-        || (rank_diff == 1 && file_diff == 2)
-        || (rank_diff == 1 && file_diff == -2)
-        || (rank_diff == -1 && file_diff == 2)
-        || (rank_diff == -1 && file_diff == -2)
+    if (rank_diff.abs() == 2 && file_diff.abs() == 1)
+        || (rank_diff.abs() == 1 && file_diff.abs() == 2)
     {
         return Ok(());
     }
@@ -101,12 +86,7 @@ pub fn is_knight_move_valid(from: u64, to: u64) -> Result<(), PieceError> {
 }
 
 pub fn is_bishop_move_valid(from: u64, to: u64) -> Result<(), PieceError> {
-    let result = basic_position_check(from, to);
-    if result.is_err() {
-        return Err(result.unwrap_err());
-    }
-
-    let (_, rank_diff, file_diff) = result.unwrap();
+    let (_, rank_diff, file_diff) = basic_position_check(from, to)?;
 
     if rank_diff == file_diff || rank_diff == -file_diff {
         return Ok(());
@@ -116,12 +96,7 @@ pub fn is_bishop_move_valid(from: u64, to: u64) -> Result<(), PieceError> {
 }
 
 pub fn is_rook_move_valid(from: u64, to: u64) -> Result<(), PieceError> {
-    let result = basic_position_check(from, to);
-    if result.is_err() {
-        return Err(result.unwrap_err());
-    }
-
-    let (_, rank_diff, file_diff) = result.unwrap();
+    let (_, rank_diff, file_diff) = basic_position_check(from, to)?;
 
     if rank_diff == 0 || file_diff == 0 {
         return Ok(());
@@ -131,12 +106,7 @@ pub fn is_rook_move_valid(from: u64, to: u64) -> Result<(), PieceError> {
 }
 
 pub fn is_queen_move_valid(from: u64, to: u64) -> Result<(), PieceError> {
-    let result = basic_position_check(from, to);
-    if result.is_err() {
-        return Err(result.unwrap_err());
-    }
-
-    let (_, rank_diff, file_diff) = result.unwrap();
+    let (_, rank_diff, file_diff) = basic_position_check(from, to)?;
 
     if rank_diff == 0 || file_diff == 0 || rank_diff == file_diff || rank_diff == -file_diff {
         return Ok(());
@@ -146,12 +116,7 @@ pub fn is_queen_move_valid(from: u64, to: u64) -> Result<(), PieceError> {
 }
 
 pub fn is_king_move_valid(from: u64, to: u64) -> Result<(), PieceError> {
-    let result = basic_position_check(from, to);
-    if result.is_err() {
-        return Err(result.unwrap_err());
-    }
-
-    let (_, rank_diff, file_diff) = result.unwrap();
+    let (_, rank_diff, file_diff) = basic_position_check(from, to)?;
 
     if rank_diff.abs() <= 1 && file_diff.abs() <= 1 {
         return Ok(());
@@ -225,13 +190,14 @@ pub fn check_pawn_move_blocked(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn check_pawn_move_blocked_for_white(
     from_rank: u64,
     rank_diff: i64,
     file_diff: i64,
     board: Bitboard,
     white_board: Player,
-    mut black_board: Player,
+    black_board: Player,
     from: u64,
     to: u64,
 ) -> bool {
@@ -269,12 +235,13 @@ fn check_pawn_move_blocked_for_white(
     false
 }
 
+#[allow(clippy::too_many_arguments)]
 fn check_pawn_move_blocked_for_black(
     from_rank: u64,
     rank_diff: i64,
     file_diff: i64,
     board: Bitboard,
-    mut white_board: Player,
+    white_board: Player,
     black_board: Player,
     from: u64,
     to: u64,
@@ -311,6 +278,7 @@ fn check_pawn_move_blocked_for_black(
     false
 }
 
+// whether the opponent, with its own pawn direction, attacks the square the king moves to
 pub fn is_king_move_blocked(
     to: u64,
     color: PlayerColor,
@@ -319,8 +287,25 @@ pub fn is_king_move_blocked(
     black_board: Player,
 ) -> bool {
     match color {
-        PlayerColor::White => check_king_in_check(to, board, black_board, color),
-        PlayerColor::Black => check_king_in_check(to, board, white_board, color),
+        PlayerColor::White => check_king_in_check(to, board, black_board, PlayerColor::Black),
+        PlayerColor::Black => check_king_in_check(to, board, white_board, PlayerColor::White),
+    }
+}
+
+pub fn is_king_attacked(chessboard: &Chessboard, color: PlayerColor) -> bool {
+    match color {
+        PlayerColor::White => check_king_in_check(
+            chessboard.white.king.get_board().trailing_zeros() as u64,
+            chessboard.get_board(),
+            chessboard.black.clone(),
+            PlayerColor::Black,
+        ),
+        PlayerColor::Black => check_king_in_check(
+            chessboard.black.king.get_board().trailing_zeros() as u64,
+            chessboard.get_board(),
+            chessboard.white.clone(),
+            PlayerColor::White,
+        ),
     }
 }
 
@@ -356,8 +341,9 @@ fn check_king_in_check(
     let mut opponent_king_moves = 0;
 
     for i in 0..NUM_SQUARES {
+        // pawns attack diagonally, the square they push to is safe
         if (opponent_pawns & (1 << i)) != 0 {
-            opponent_pawn_moves |= get_pawn_moves(i, opponent_color);
+            opponent_pawn_moves |= get_pawn_attacks(i, opponent_color);
         }
 
         if (opponent_knights & (1 << i)) != 0 {
@@ -424,7 +410,7 @@ pub fn get_pawn_moves(pos: u64, color: PlayerColor) -> u64 {
         }
     }
 
-    if pos % 8 != 0 {
+    if !pos.is_multiple_of(8) {
         match color {
             PlayerColor::White => {
                 if pos < 56 {
@@ -457,6 +443,17 @@ pub fn get_pawn_moves(pos: u64, color: PlayerColor) -> u64 {
     moves
 }
 
+// diagonal captures only, without the forward push
+pub fn get_pawn_attacks(pos: u64, color: PlayerColor) -> u64 {
+    let forward = match color {
+        PlayerColor::White if pos < 56 => 1 << (pos + 8),
+        PlayerColor::Black if pos > 7 => 1 << (pos - 8),
+        _ => 0,
+    };
+
+    get_pawn_moves(pos, color) & !forward
+}
+
 pub fn get_knight_moves(pos: u64) -> u64 {
     let mut moves = 0;
 
@@ -470,7 +467,7 @@ pub fn get_knight_moves(pos: u64) -> u64 {
         }
     }
 
-    if pos % 8 > 0 {
+    if !pos.is_multiple_of(8) {
         if pos < 48 {
             moves |= 1 << (pos + 15);
         }
@@ -606,7 +603,7 @@ pub fn get_king_moves(pos: u64) -> u64 {
         moves |= 1 << (pos - 8);
     }
 
-    if pos % 8 != 0 {
+    if !pos.is_multiple_of(8) {
         if pos < 56 {
             moves |= 1 << (pos + 7);
         }
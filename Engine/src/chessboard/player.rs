@@ -3,13 +3,24 @@ use crate::constants::BOARD_SIZE;
 use crate::exceptions::{BitboardError, PieceError};
 use crate::chessboard::piece::PieceType;
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum PlayerColor {
     White,
     Black,
 }
 
+impl PlayerColor {
+    pub fn opposite(&self) -> PlayerColor {
+        match self {
+            PlayerColor::White => PlayerColor::Black,
+            PlayerColor::Black => PlayerColor::White,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Player {
     pub color: PlayerColor,
     pub pieces: Bitboard,
@@ -119,10 +130,54 @@ impl Player {
         }
     }
 
+    pub fn empty(color: PlayerColor) -> Player {
+        Player {
+            color,
+            pieces: Bitboard::new(),
+            pawns: Bitboard::new(),
+            knights: Bitboard::new(),
+            bishops: Bitboard::new(),
+            rooks: Bitboard::new(),
+            queen: Bitboard::new(),
+            king: Bitboard::new(),
+            has_left_rook_moved: true,
+            has_right_rook_moved: true,
+            has_king_moved: true,
+        }
+    }
+
     pub fn get_board(&self) -> u64 {
         self.pieces.get_board()
     }
 
+    pub fn add_piece(&mut self, position: u64, piece_type: PieceType) -> Result<(), BitboardError> {
+        if self.pieces.get_square(position) {
+            return Err(BitboardError::SquareOccupied);
+        }
+
+        match piece_type {
+            PieceType::Pawn => self.pawns.set_square(position),
+            PieceType::Knight => self.knights.set_square(position),
+            PieceType::Bishop => self.bishops.set_square(position),
+            PieceType::Rook => self.rooks.set_square(position),
+            PieceType::Queen => self.queen.set_square(position),
+            PieceType::King => self.king.set_square(position),
+            PieceType::None => return Err(BitboardError::PieceNotFound),
+        }
+
+        self.pieces.set_square(position);
+        Ok(())
+    }
+
+    // right rook = h-file rook, left rook = a-file rook
+    pub fn can_castle_kingside(&self) -> bool {
+        !self.has_king_moved && !self.has_right_rook_moved
+    }
+
+    pub fn can_castle_queenside(&self) -> bool {
+        !self.has_king_moved && !self.has_left_rook_moved
+    }
+
     pub fn make_move(&mut self, from: u64, to: u64) -> Result<(), BitboardError> {
         self.pieces.clear_square(from);
         self.pieces.set_square(to);
@@ -288,7 +343,7 @@ impl Player {
         Ok(())
     }
 
-    pub fn get_piece_type(&mut self, position: u64) -> Result<PieceType, PieceError> {
+    pub fn get_piece_type(&self, position: u64) -> Result<PieceType, PieceError> {
         if self.pawns.get_square(position) {
             return Ok(PieceType::Pawn);
         }
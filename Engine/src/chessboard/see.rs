@@ -0,0 +1,149 @@
+use crate::chessboard::chessboard::Chessboard;
+use crate::chessboard::piece::{
+    get_bishop_moves, get_king_moves, get_knight_moves, get_pawn_attacks, get_rook_moves, PieceType,
+};
+use crate::chessboard::player::{Player, PlayerColor};
+use crate::constants::BOARD_SIZE;
+use crate::evaluator::{piece_value, QUEEN_VALUE};
+
+// the king is worth more than everything else, so it only captures last
+const SEE_KING_VALUE: i64 = 10 * QUEEN_VALUE;
+
+// cheapest first, the order attackers are taken in
+const EXCHANGE_ORDER: [PieceType; 6] = [
+    PieceType::Pawn,
+    PieceType::Knight,
+    PieceType::Bishop,
+    PieceType::Rook,
+    PieceType::Queen,
+    PieceType::King,
+];
+
+impl Chessboard {
+    // Static exchange evaluation: the material the side making the capture wins (or loses,
+    // when negative) if both sides keep recapturing on the target square with their least
+    // valuable attacker, each free to stop when going on would cost it. Sliders behind the
+    // pieces that capture join in as they are uncovered. Promotions are not counted.
+    pub fn see(&self, next_move: (u64, u64)) -> i64 {
+        let (from, to) = next_move;
+        let mut color = match self.white.has_piece_on(from) {
+            true => PlayerColor::White,
+            false => PlayerColor::Black,
+        };
+
+        let mut attacker = self.get_piece_type_color(color, from);
+        let mut victim = self.get_piece_type_color(color.opposite(), to);
+        let mut occupied = self.get_board() & !(1 << from);
+        if attacker == PieceType::Pawn && victim == PieceType::None && from % BOARD_SIZE != to % BOARD_SIZE {
+            // en passant, the pawn taken is beside the target square
+            victim = PieceType::Pawn;
+            let captured = match color {
+                PlayerColor::White => to - BOARD_SIZE,
+                PlayerColor::Black => to + BOARD_SIZE,
+            };
+            occupied &= !(1 << captured);
+        }
+
+        // what each capture of the exchange takes, in order
+        let mut captured_values = vec![see_value(victim)];
+        loop {
+            color = color.opposite();
+            let attackers = self.attackers_to(to, occupied, color);
+            let Some((square, piece_type)) = self.least_valuable(attackers, color) else {
+                break;
+            };
+
+            // a king can't capture on a square the other side still attacks
+            if piece_type == PieceType::King
+                && self.attackers_to(to, occupied & !(1 << square), color.opposite()) != 0
+            {
+                break;
+            }
+
+            captured_values.push(see_value(attacker));
+            occupied &= !(1 << square);
+            attacker = piece_type;
+        }
+
+        // from the last capture back: a recapture is only made when it gains something
+        let recaptures = captured_values[1..]
+            .iter()
+            .rev()
+            .fold(0, |gain, value| (value - gain).max(0));
+
+        captured_values[0] - recaptures
+    }
+
+    // Whether the capture wins at least `threshold`, without playing out the exchange
+    // when losing the capturing piece for its victim already settles it.
+    pub fn see_at_least(&self, next_move: (u64, u64), threshold: i64) -> bool {
+        let (from, to) = next_move;
+        let color = match self.white.has_piece_on(from) {
+            true => PlayerColor::White,
+            false => PlayerColor::Black,
+        };
+        let attacker = see_value(self.get_piece_type_color(color, from));
+        let victim = see_value(self.get_piece_type_color(color.opposite(), to));
+
+        if victim - attacker >= threshold {
+            return true;
+        }
+
+        self.see(next_move) >= threshold
+    }
+
+    // The pieces of `color` attacking `square` on the given occupancy, so pieces already
+    // taken off by an exchange no longer attack and the sliders behind them do.
+    fn attackers_to(&self, square: u64, occupied: u64, color: PlayerColor) -> u64 {
+        let player = match color {
+            PlayerColor::White => &self.white,
+            PlayerColor::Black => &self.black,
+        };
+        let diagonal = player.bishops.get_board() | player.queen.get_board();
+        let straight = player.rooks.get_board() | player.queen.get_board();
+
+        // a pawn attacks the square when a pawn of the other colour on it would attack the pawn
+        let attackers = (get_pawn_attacks(square, color.opposite()) & player.pawns.get_board())
+            | (get_knight_moves(square) & player.knights.get_board())
+            | (get_bishop_moves(square, occupied) & diagonal)
+            | (get_rook_moves(square, occupied) & straight)
+            | (get_king_moves(square) & player.king.get_board());
+
+        attackers & occupied
+    }
+
+    fn least_valuable(&self, attackers: u64, color: PlayerColor) -> Option<(u64, PieceType)> {
+        let player = match color {
+            PlayerColor::White => &self.white,
+            PlayerColor::Black => &self.black,
+        };
+
+        EXCHANGE_ORDER.iter().find_map(|piece_type| {
+            let pieces = attackers & piece_board(player, *piece_type);
+
+            match pieces {
+                0 => None,
+                pieces => Some((pieces.trailing_zeros() as u64, *piece_type)),
+            }
+        })
+    }
+}
+
+fn piece_board(player: &Player, piece_type: PieceType) -> u64 {
+    match piece_type {
+        PieceType::Pawn => player.pawns.get_board(),
+        PieceType::Knight => player.knights.get_board(),
+        PieceType::Bishop => player.bishops.get_board(),
+        PieceType::Rook => player.rooks.get_board(),
+        PieceType::Queen => player.queen.get_board(),
+        PieceType::King => player.king.get_board(),
+        PieceType::None => 0,
+    }
+}
+
+fn see_value(piece_type: PieceType) -> i64 {
+    match piece_type {
+        PieceType::King => SEE_KING_VALUE,
+        piece_type => piece_value(piece_type),
+    }
+}
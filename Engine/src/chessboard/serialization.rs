@@ -0,0 +1,89 @@
+use serde::de::Error;
+use serde::ser::Error as SerError;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use crate::chessboard::chessboard::Chessboard;
+use crate::chessboard::packed::PackedPosition;
+
+// Human-readable formats (JSON, ...) store a board as its FEN string,
+// binary formats store its 30 byte packed encoding.
+
+impl Serialize for Chessboard {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            return serializer.serialize_str(&self.to_fen());
+        }
+
+        self.to_packed()
+            .map_err(|e| S::Error::custom(format!("cannot pack position: {:?}", e)))?
+            .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Chessboard {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Chessboard, D::Error> {
+        if deserializer.is_human_readable() {
+            let fen = String::deserialize(deserializer)?;
+            return Chessboard::from_fen(&fen)
+                .map_err(|e| D::Error::custom(format!("invalid FEN {:?}: {:?}", fen, e)));
+        }
+
+        let packed = PackedPosition::deserialize(deserializer)?;
+        Chessboard::from_packed(&packed)
+            .map_err(|e| D::Error::custom(format!("invalid packed position: {:?}", e)))
+    }
+}
+
+// (from, to) moves as "e2e4" strings in human-readable formats, as tuples otherwise.
+// Use with #[serde(with = "crate::chessboard::serialization::square_move")].
+pub mod square_move {
+    use crate::chessboard::chessboard::Chessboard;
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        next_move: &(u64, u64),
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            return serializer.serialize_str(&Chessboard::convert_move_to_string(*next_move));
+        }
+
+        next_move.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<(u64, u64), D::Error> {
+        if deserializer.is_human_readable() {
+            let notation = String::deserialize(deserializer)?;
+            return Chessboard::parse_move_string(&notation)
+                .ok_or_else(|| D::Error::custom(format!("invalid move {:?}", notation)));
+        }
+
+        <(u64, u64)>::deserialize(deserializer)
+    }
+}
+
+pub mod move_list {
+    use serde::ser::SerializeSeq;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Serialize, Deserialize)]
+    struct Move(#[serde(with = "super::square_move")] (u64, u64));
+
+    pub fn serialize<S: Serializer>(
+        moves: &[(u64, u64)],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(moves.len()))?;
+        for next_move in moves {
+            seq.serialize_element(&Move(*next_move))?;
+        }
+        seq.end()
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<(u64, u64)>, D::Error> {
+        let moves = Vec::<Move>::deserialize(deserializer)?;
+        Ok(moves.into_iter().map(|next_move| next_move.0).collect())
+    }
+}
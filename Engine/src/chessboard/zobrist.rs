@@ -0,0 +1,86 @@
+use crate::chessboard::bitboard::Bitboard;
+use crate::chessboard::chessboard::Chessboard;
+use crate::chessboard::player::{Player, PlayerColor};
+use crate::constants::{BOARD_SIZE, NUM_SQUARES};
+
+// 12 piece kinds x 64 squares, 4 castling rights, 8 en passant files, side to move
+const NUM_KEYS: usize = 12 * NUM_SQUARES as usize + 4 + 8 + 1;
+const CASTLING_OFFSET: usize = 12 * NUM_SQUARES as usize;
+const EN_PASSANT_OFFSET: usize = CASTLING_OFFSET + 4;
+const SIDE_TO_MOVE_OFFSET: usize = EN_PASSANT_OFFSET + 8;
+const SEED: u64 = 0x2545_F491_4F6C_DD1D;
+
+const KEYS: [u64; NUM_KEYS] = generate_keys();
+
+const fn generate_keys() -> [u64; NUM_KEYS] {
+    // splitmix64
+    let mut keys = [0; NUM_KEYS];
+    let mut state = SEED;
+    let mut i = 0;
+    while i < NUM_KEYS {
+        state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        keys[i] = z ^ (z >> 31);
+        i += 1;
+    }
+    keys
+}
+
+impl Chessboard {
+    pub fn zobrist_hash(&self) -> u64 {
+        let mut hash = hash_player(&self.white, 0) ^ hash_player(&self.black, 6);
+
+        if self.white.can_castle_kingside() && self.white.rooks.get_square(7) {
+            hash ^= KEYS[CASTLING_OFFSET];
+        }
+        if self.white.can_castle_queenside() && self.white.rooks.get_square(0) {
+            hash ^= KEYS[CASTLING_OFFSET + 1];
+        }
+        if self.black.can_castle_kingside() && self.black.rooks.get_square(63) {
+            hash ^= KEYS[CASTLING_OFFSET + 2];
+        }
+        if self.black.can_castle_queenside() && self.black.rooks.get_square(56) {
+            hash ^= KEYS[CASTLING_OFFSET + 3];
+        }
+
+        if let Some(square) = self.en_passant {
+            hash ^= KEYS[EN_PASSANT_OFFSET + (square % BOARD_SIZE) as usize];
+        }
+
+        if self.side_to_move == PlayerColor::Black {
+            hash ^= KEYS[SIDE_TO_MOVE_OFFSET];
+        }
+
+        hash
+    }
+}
+
+fn hash_player(player: &Player, first_kind: usize) -> u64 {
+    let bitboards = [
+        player.pawns,
+        player.knights,
+        player.bishops,
+        player.rooks,
+        player.queen,
+        player.king,
+    ];
+
+    let mut hash = 0;
+    for (kind, bitboard) in bitboards.iter().enumerate() {
+        hash ^= hash_bitboard(*bitboard, first_kind + kind);
+    }
+    hash
+}
+
+fn hash_bitboard(bitboard: Bitboard, kind: usize) -> u64 {
+    let mut hash = 0;
+    let mut remaining = bitboard.get_board();
+    while remaining != 0 {
+        let square = remaining.trailing_zeros() as usize;
+        remaining &= remaining - 1;
+        hash ^= KEYS[kind * NUM_SQUARES as usize + square];
+    }
+    hash
+}
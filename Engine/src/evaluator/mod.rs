@@ -1,15 +1,15 @@
 use crate::chessboard::chessboard::Chessboard;
 use crate::chessboard::player::PlayerColor;
-use crate::constants::NUM_SQUARES;
 use crate::chessboard::piece::{ PieceType};
 
-const PAWN_VALUE: i64 = 1;
-const KNIGHT_VALUE: i64 = 3;
-const BISHOP_VALUE: i64 = 3;
-const ROOK_VALUE: i64 = 5;
-const QUEEN_VALUE: i64 = 9;
-const KING_VALUE: i64 = 0;
-const NONE_VALUE: i64 = 0;
+// centipawns
+pub const PAWN_VALUE: i64 = 100;
+pub const KNIGHT_VALUE: i64 = 300;
+pub const BISHOP_VALUE: i64 = 300;
+pub const ROOK_VALUE: i64 = 500;
+pub const QUEEN_VALUE: i64 = 900;
+pub const KING_VALUE: i64 = 0;
+pub const NONE_VALUE: i64 = 0;
 const WHITE_ADVANTAGE: i64 = 10;
 
 
@@ -24,24 +24,29 @@ pub fn evaluate(state: & Chessboard, player_color: PlayerColor) -> i64 {
     }
 }
 
-    fn calculate_material(state: &Chessboard, player_color: PlayerColor) -> i64 {
-
-        let mut material_value = 0;
-
-        for index in 0..NUM_SQUARES {
-            let piece_type = state.get_piece_type_color(player_color, index);
+pub fn piece_value(piece_type: PieceType) -> i64 {
+    match piece_type {
+        PieceType::Pawn => PAWN_VALUE,
+        PieceType::Knight => KNIGHT_VALUE,
+        PieceType::Bishop => BISHOP_VALUE,
+        PieceType::Rook => ROOK_VALUE,
+        PieceType::Queen => QUEEN_VALUE,
+        PieceType::King => KING_VALUE,
+        PieceType::None => NONE_VALUE,
+    }
+}
 
-            material_value += match piece_type {
-                PieceType::Pawn => PAWN_VALUE,
-                PieceType::Knight => KNIGHT_VALUE,
-                PieceType::Bishop => BISHOP_VALUE,
-                PieceType::Rook => ROOK_VALUE,
-                PieceType::Queen => QUEEN_VALUE,
-                PieceType::King => KING_VALUE,
-                PieceType::None => NONE_VALUE,
-            };
-            
-        }
+    fn calculate_material(state: &Chessboard, player_color: PlayerColor) -> i64 {
 
-        material_value
+        let player = match player_color {
+            PlayerColor::White => &state.white,
+            PlayerColor::Black => &state.black,
+        };
+
+        player.pawns.get_num_squares() as i64 * PAWN_VALUE
+            + player.knights.get_num_squares() as i64 * KNIGHT_VALUE
+            + player.bishops.get_num_squares() as i64 * BISHOP_VALUE
+            + player.rooks.get_num_squares() as i64 * ROOK_VALUE
+            + player.queen.get_num_squares() as i64 * QUEEN_VALUE
+            + player.king.get_num_squares() as i64 * KING_VALUE
     }
@@ -25,3 +25,67 @@ pub enum MoveError {
     KingInCheck,
     PieceNotFound,
 }
+
+#[derive(Debug, PartialOrd, PartialEq)]
+pub enum FenError {
+    WrongFieldCount,
+    InvalidPiecePlacement,
+    InvalidSideToMove,
+    InvalidCastling,
+    InvalidEnPassant,
+    InvalidClock,
+}
+
+#[derive(Debug, PartialOrd, PartialEq)]
+pub enum PackedPositionError {
+    TooManyPieces,
+    ClockOverflow,
+    InvalidPieceCode,
+    InvalidEnPassant,
+    // each side needs exactly one king
+    InvalidKingCount,
+}
+
+#[derive(Debug, PartialOrd, PartialEq)]
+pub enum SanError {
+    InvalidSyntax,
+    IllegalMove,
+    AmbiguousMove,
+    UnsupportedPromotion,
+}
+
+#[derive(Debug, PartialOrd, PartialEq)]
+pub enum EpdError {
+    InvalidPosition(FenError),
+    InvalidOperation,
+    UnterminatedString,
+    InvalidMove(SanError),
+}
+
+#[derive(Debug, PartialOrd, PartialEq)]
+pub enum OptionError {
+    UnknownOption,
+    InvalidValue,
+}
+
+#[derive(Debug, PartialOrd, PartialEq)]
+pub enum TablebaseError {
+    DirectoryNotFound,
+    NoTables,
+}
+
+#[derive(Debug, PartialOrd, PartialEq)]
+pub enum BookError {
+    FileNotFound,
+    InvalidSize,
+    WriteFailed,
+}
+
+#[derive(Debug, PartialOrd, PartialEq)]
+pub enum PgnError {
+    InvalidTag,
+    UnterminatedComment,
+    UnterminatedVariation,
+    InvalidPosition(FenError),
+    InvalidMove(SanError),
+}
@@ -1,9 +1,22 @@
 #![allow(dead_code)]
+#![allow(clippy::module_inception)]
 
-use std::io;
+use std::{env, fs, io, thread};
+use std::time::{Duration, Instant};
 use crate::chessboard::chessboard::Chessboard;
 use crate::chessboard::player::PlayerColor;
-use crate::min_max::min_max::get_best_move;
+use crate::min_max::benchmark::{signature, time_to_depth, BENCHMARK_DEPTH, SIGNATURE_DEPTH};
+use crate::min_max::min_max::{book_move, search_with_skill, SearchResult, FIFTY_MOVE_PLIES};
+use crate::min_max::move_ordering::MoveOrderer;
+use crate::min_max::next_move_generator::generate_next_moves;
+use crate::min_max::search_limits::{SearchLimits, StopFlag};
+use crate::min_max::search_info::SearchInfo;
+use crate::min_max::search_options::SearchOptions;
+use crate::min_max::transposition_table::{TranspositionTable, DEFAULT_SIZE_MB};
+use crate::notation::book_builder::{BookBuilder, BookFilters};
+
+const COMPUTER_CLOCK: Duration = Duration::from_secs(300);
+const COMPUTER_INCREMENT: Duration = Duration::from_secs(2);
 
 mod constants;
 mod exceptions;
@@ -11,57 +24,298 @@ mod chessboard;
 mod tests;
 mod min_max;
 mod evaluator;
+mod notation;
+
+// `Engine [white|black|self] [ponder] [book <file>] [skill <0-20>|elo <rating>] [seed <n>]` plays the computer
+// as white (the default), as black, or against itself
+// `Engine bench [depth] [threads]` measures time to depth for 1 to threads search threads instead
+// `Engine makebook <book.bin> <games.pgn>... [minrating=N] [maxply=N] [minoccurrences=N]` builds a Polyglot book
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let available_threads = thread::available_parallelism().map_or(1, |threads| threads.get());
+
+    if args.get(1).is_some_and(|arg| arg == "bench") {
+        let depth = args.get(2).and_then(|depth| depth.parse().ok()).unwrap_or(BENCHMARK_DEPTH);
+        let threads = args.get(3).and_then(|threads| threads.parse().ok()).unwrap_or(available_threads);
+        run_benchmark(depth, threads);
+        return;
+    }
+
+    if args.get(1).is_some_and(|arg| arg == "makebook") {
+        match args.get(2) {
+            Some(path) => make_book(path, &args[3..]),
+            None => println!("Usage: Engine makebook <book.bin> <games.pgn>... [minrating=N] [maxply=N] [minoccurrences=N]"),
+        }
+        return;
+    }
+
+    let computer_colors = match args.get(1).map(String::as_str) {
+        Some("black") => vec![PlayerColor::Black],
+        Some("self") => vec![PlayerColor::White, PlayerColor::Black],
+        _ => vec![PlayerColor::White],
+    };
+    // `Engine white ponder` or `Engine black ponder` thinks on the human's time too
+    let ponder = args.iter().skip(1).any(|arg| arg == "ponder");
 
-// convention: Computer plays white
-#[tokio::main]
-async fn main() {
     let mut chessboard = Chessboard::new();
+    let mut white_clock = COMPUTER_CLOCK;
+    let mut black_clock = COMPUTER_CLOCK;
+    let transposition_table = TranspositionTable::new(DEFAULT_SIZE_MB);
+    let move_orderer = MoveOrderer::new();
+    let mut search_options = SearchOptions {
+        threads: available_threads,
+        ..SearchOptions::default()
+    };
+    // `book <file>` plays the openings from a Polyglot book
+    if let Some(path) = args.iter().position(|arg| arg == "book").and_then(|i| args.get(i + 1)) {
+        if search_options.set_option("BookFile", path).is_err() {
+            println!("Can't read the book {}", path);
+        }
+    }
+    // `skill 5`, `elo 1200` or `seed 42` set the option of the same name
+    for (arg, option) in [("skill", "SkillLevel"), ("elo", "Elo"), ("seed", "Seed")] {
+        if let Some(value) = args.iter().position(|name| name == arg).and_then(|i| args.get(i + 1)) {
+            if search_options.set_option(option, value).is_err() {
+                println!("Invalid {} {}", arg, value);
+            }
+        }
+    }
+    // the reply the last principal variation expects, and the search done on it if it came
+    let mut prediction = None;
+    let mut pondered = None;
+    // the keys of the positions played so far, for repetitions
+    let mut history = vec![];
 
-    let mut player_color = PlayerColor::White;
     while !chessboard.is_finished() {
         chessboard.print_board();
+        let key = chessboard.zobrist_hash();
+        if chessboard.halfmove_clock >= FIFTY_MOVE_PLIES || history.iter().filter(|earlier| **earlier == key).count() >= 2 {
+            println!("Draw");
+            break;
+        }
+
+        let player_color = chessboard.side_to_move;
+        // neither side may search or be asked for a move it doesn't have
+        if generate_next_moves(&chessboard, player_color).is_empty() {
+            match (chessboard.is_in_check(player_color), player_color) {
+                (true, PlayerColor::White) => println!("Checkmate, black wins"),
+                (true, PlayerColor::Black) => println!("Checkmate, white wins"),
+                (false, _) => println!("Stalemate"),
+            }
+            break;
+        }
 
-        match player_color {
-            PlayerColor::White => {
-                println!("Computer moves...");
-                let chessboard_copy = chessboard.clone();
+        let limits = SearchLimits {
+            game_history: history.clone(),
+            ..SearchLimits::clock(white_clock, black_clock, COMPUTER_INCREMENT, COMPUTER_INCREMENT, None)
+        };
+        history.push(key);
 
-                let start = std::time::Instant::now();
-                let best_move = get_best_move(&chessboard_copy).await;
-                let duration = start.elapsed();
-                println!("Time elapsed: {} ms", duration.as_millis());
-                println!("{:?}", best_move);
+        if computer_colors.contains(&player_color) {
+            println!("Computer moves...");
 
-                let from = Chessboard::convert_index_to_square(best_move.0);
-                let to = Chessboard::convert_index_to_square(best_move.1);
+            // no thinking, and no pondering, while in the book
+            if let Some(next_move) = book_move(&chessboard, &search_options) {
+                println!("bookmove {}", Chessboard::convert_move_to_string(next_move));
+                let from = Chessboard::convert_index_to_square(next_move.0);
+                let to = Chessboard::convert_index_to_square(next_move.1);
 
-                chessboard.perform_move(&from, &to, PlayerColor::White).unwrap();
-                player_color = PlayerColor::Black
+                chessboard.perform_move(&from, &to, player_color).unwrap();
+                pondered = None;
+                continue;
             }
-            PlayerColor::Black => {
-                loop {
-                    let mut from = String::new();
-                    let mut to = String::new();
-
-                    println!("Enter the from position:");
-                    io::stdin().read_line(&mut from).expect("Failed to read line");
-
-                    println!("Enter the to position:");
-                    io::stdin().read_line(&mut to).expect("Failed to read line");
-
-                    let from = from.trim();
-                    let to = to.trim();
-
-                    if chessboard.perform_move(from, to, PlayerColor::Black).is_ok() {
-                        player_color = PlayerColor::White;
-                        break;
-                    }
-                    else {
-                        println!("Invalid move! Try again");
-                    }
+
+            let (result, duration) = match pondered.take() {
+                Some(pondered) => pondered,
+                None => {
+                    let start = Instant::now();
+                    let result = search_with_skill(
+                        &chessboard,
+                        &limits,
+                        &search_options,
+                        &transposition_table,
+                        &move_orderer,
+                        &StopFlag::new(),
+                        Some(&|info: &SearchInfo| println!("info {}", info))
+                    );
+                    (result, start.elapsed())
                 }
+            };
+            let best_move = result.best_move;
+            let computer_clock = match player_color {
+                PlayerColor::White => &mut white_clock,
+                PlayerColor::Black => &mut black_clock,
+            };
+            *computer_clock = computer_clock.saturating_sub(duration) + COMPUTER_INCREMENT;
+            println!("Time elapsed: {} ms", duration.as_millis());
+            println!("Clock: {} s", computer_clock.as_secs());
+            print!("{}", result.stats);
+            println!("bestmove {}", Chessboard::convert_move_to_string(best_move));
+
+            let from = Chessboard::convert_index_to_square(best_move.0);
+            let to = Chessboard::convert_index_to_square(best_move.1);
+
+            chessboard.perform_move(&from, &to, player_color).unwrap();
+            prediction = result.principal_variation.get(1).copied();
+        } else {
+            let next_move = match prediction.take() {
+                Some(predicted_move) if ponder => {
+                    let (next_move, ponder_result) = ponder_on(
+                        &chessboard,
+                        predicted_move,
+                        &limits,
+                        &search_options,
+                        &transposition_table,
+                        &move_orderer
+                    );
+                    pondered = ponder_result;
+                    next_move
+                }
+                _ => read_move(&chessboard, player_color),
+            };
+
+            let from = Chessboard::convert_index_to_square(next_move.0);
+            let to = Chessboard::convert_index_to_square(next_move.1);
+
+            chessboard.perform_move(&from, &to, player_color).unwrap();
+        }
+    }
+}
+
+// asks until the human enters a legal move
+fn read_move(chessboard: &Chessboard, player_color: PlayerColor) -> (u64, u64) {
+    loop {
+        let mut from = String::new();
+        let mut to = String::new();
+
+        println!("Enter the from position:");
+        io::stdin().read_line(&mut from).expect("Failed to read line");
+
+        println!("Enter the to position:");
+        io::stdin().read_line(&mut to).expect("Failed to read line");
+
+        let from = from.trim();
+        let to = to.trim();
+
+        if chessboard.clone().perform_move(from, to, player_color).is_ok() {
+            return (Chessboard::convert_square_to_index(from), Chessboard::convert_square_to_index(to));
+        }
+        else {
+            println!("Invalid move! Try again");
+        }
+    }
+}
+
+// Searches the position after the predicted reply while the human thinks. On a ponder hit
+// the search goes on under the computer's clock and its result, with the time it took
+// after the hit, is the computer's next move. On a miss it is thrown away, the table
+// keeps what it learned.
+fn ponder_on(
+    chessboard: &Chessboard,
+    predicted_move: (u64, u64),
+    limits: &SearchLimits,
+    search_options: &SearchOptions,
+    transposition_table: &TranspositionTable,
+    move_orderer: &MoveOrderer
+) -> ((u64, u64), Option<(SearchResult, Duration)>) {
+    let player_color = chessboard.side_to_move;
+    let mut ponder_board = chessboard.clone();
+    let from = Chessboard::convert_index_to_square(predicted_move.0);
+    let to = Chessboard::convert_index_to_square(predicted_move.1);
+    if ponder_board.perform_move(&from, &to, player_color).is_err() {
+        return (read_move(chessboard, player_color), None);
+    }
+    let mut ponder_limits = limits.clone();
+    ponder_limits.game_history.push(chessboard.zobrist_hash());
+
+    let stop_flag = StopFlag::pondering();
+    thread::scope(|scope| {
+        let reader = scope.spawn(|| {
+            let next_move = read_move(chessboard, player_color);
+            if next_move == predicted_move {
+                stop_flag.ponder_hit();
+            } else {
+                stop_flag.stop();
+            }
+            (next_move, Instant::now())
+        });
+
+        let result = search_with_skill(
+            &ponder_board,
+            &ponder_limits,
+            search_options,
+            transposition_table,
+            move_orderer,
+            &stop_flag,
+            None
+        );
+        let (next_move, answered) = reader.join().unwrap();
+
+        if next_move == predicted_move {
+            println!("Ponder hit");
+            (next_move, Some((result, answered.elapsed())))
+        } else {
+            (next_move, None)
+        }
+    })
+}
+
+fn run_benchmark(depth: usize, threads: usize) {
+    println!("Signature at depth {}: {} nodes", SIGNATURE_DEPTH, signature(SIGNATURE_DEPTH));
+    println!("Time to depth {}:", depth);
+    let results = time_to_depth(depth, threads);
+    for result in results.iter() {
+        println!(
+            "{:>2} threads: {:>6} ms, speedup {:.2}",
+            result.threads,
+            result.time.as_millis(),
+            result.speedup(&results[0])
+        );
+    }
+}
+
+fn make_book(path: &str, args: &[String]) {
+    let mut filters = BookFilters::default();
+    let mut pgn_files = vec![];
+    for arg in args {
+        let Some((name, value)) = arg.split_once('=') else {
+            pgn_files.push(arg);
+            continue;
+        };
+        // each value parsed as its filter's own type, so out of range values are invalid too
+        let valid = match name {
+            "minrating" => value.parse().map(|value| filters.min_rating = value).is_ok(),
+            "maxply" => value.parse().map(|value| filters.max_ply = value).is_ok(),
+            "minoccurrences" => value.parse().map(|value| filters.min_occurrences = value).is_ok(),
+            _ => {
+                println!("Unknown filter {}", name);
+                continue;
             }
+        };
+        if !valid {
+            println!("Invalid {} {}", name, value);
+        }
+    }
+
+    let mut builder = BookBuilder::new(filters);
+    for pgn_file in pgn_files {
+        let added = fs::read_to_string(pgn_file)
+            .map_err(|error| error.to_string())
+            .and_then(|contents| builder.add_pgn(&contents).map_err(|error| format!("{:?}", error)));
+        if let Err(error) = added {
+            println!("Can't read {}: {}", pgn_file, error);
         }
     }
-    //chessboard.perform_move()
+
+    let book = builder.build();
+    match book.save(path) {
+        Ok(()) => println!(
+            "{} games added, {} skipped, {} entries written to {}",
+            builder.games_added,
+            builder.games_skipped,
+            book.entries().len(),
+            path
+        ),
+        Err(error) => println!("Can't write {}: {:?}", path, error),
+    }
 }
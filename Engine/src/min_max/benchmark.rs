@@ -0,0 +1,86 @@
+use std::time::{Duration, Instant};
+use crate::chessboard::chessboard::Chessboard;
+use crate::chessboard::fen::STARTING_FEN;
+use crate::min_max::min_max::search;
+use crate::min_max::move_ordering::MoveOrderer;
+use crate::min_max::search_limits::{SearchLimits, StopFlag};
+use crate::min_max::search_options::SearchOptions;
+use crate::min_max::transposition_table::{TranspositionTable, DEFAULT_SIZE_MB};
+
+pub const BENCHMARK_DEPTH: usize = 12;
+// shallow enough for the signature to be checked in the tests
+pub const SIGNATURE_DEPTH: usize = 6;
+
+const BENCHMARK_POSITIONS: [&str; 4] = [
+    STARTING_FEN,
+    "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1",
+    "r1bqk1nr/pppp1ppp/2n5/2b1p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 4 4",
+    "r2q1rk1/ppp2ppp/2np1n2/2b1p3/2B1P1b1/2NP1N2/PPP2PPP/R1BQ1RK1 w - - 2 8",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BenchmarkResult {
+    pub threads: usize,
+    // total over all benchmark positions
+    pub time: Duration,
+}
+
+impl BenchmarkResult {
+    pub fn speedup(&self, single_thread: &BenchmarkResult) -> f64 {
+        single_thread.time.as_secs_f64() / self.time.as_secs_f64().max(f64::EPSILON)
+    }
+}
+
+// Total nodes of a deterministic search of every benchmark position to the given depth.
+// Any change to what the search visits changes it, so it is checked in the tests and
+// a change that should be a pure speedup must leave it alone.
+pub fn signature(depth: usize) -> u64 {
+    let options = SearchOptions::deterministic();
+    let limits = SearchLimits::depth(depth);
+
+    BENCHMARK_POSITIONS
+        .iter()
+        .map(|fen| {
+            let position = Chessboard::from_fen(fen).unwrap();
+            let transposition_table = TranspositionTable::new(DEFAULT_SIZE_MB);
+            let move_orderer = MoveOrderer::new();
+
+            search(&position, &limits, &options, &transposition_table, &move_orderer, &StopFlag::new(), None)
+                .stats
+                .total_nodes()
+        })
+        .sum()
+}
+
+// Time to reach the given depth on every benchmark position with 1 to max_threads
+// threads, each run starting from an empty table so runs don't help each other.
+pub fn time_to_depth(depth: usize, max_threads: usize) -> Vec<BenchmarkResult> {
+    let positions: Vec<Chessboard> = BENCHMARK_POSITIONS
+        .iter()
+        .map(|fen| Chessboard::from_fen(fen).unwrap())
+        .collect();
+
+    (1..=max_threads.max(1))
+        .map(|threads| {
+            let options = SearchOptions {
+                threads,
+                ..SearchOptions::default()
+            };
+            let limits = SearchLimits::depth(depth);
+
+            let time = positions
+                .iter()
+                .map(|position| {
+                    let transposition_table = TranspositionTable::new(DEFAULT_SIZE_MB);
+                    let move_orderer = MoveOrderer::new();
+
+                    let start = Instant::now();
+                    search(position, &limits, &options, &transposition_table, &move_orderer, &StopFlag::new(), None);
+                    start.elapsed()
+                })
+                .sum();
+
+            BenchmarkResult { threads, time }
+        })
+        .collect()
+}
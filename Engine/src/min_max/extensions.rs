@@ -0,0 +1,24 @@
+use crate::chessboard::chessboard::Chessboard;
+use crate::chessboard::piece::PieceType;
+use crate::chessboard::player::PlayerColor;
+use crate::constants::BOARD_SIZE;
+
+// total plies a single line may be extended by
+pub const MAX_EXTENSIONS: usize = 16;
+pub const SINGULAR_MIN_DEPTH: usize = 6;
+// how much shallower the hash move's entry may be than the node it is extended at
+pub const SINGULAR_DEPTH_MARGIN: usize = 3;
+// centipawns per ply every other move must stay below the hash move's score
+pub const SINGULAR_MARGIN: i64 = 2;
+
+// A pawn pushed to its seventh rank. Nothing can stand in front of it any more,
+// so it is always a passed pawn one step from promoting.
+pub fn is_passed_pawn_push(state: &Chessboard, next_move: (u64, u64), player_color: PlayerColor) -> bool {
+    let (from, to) = next_move;
+    let seventh_rank = match player_color {
+        PlayerColor::White => BOARD_SIZE - 2,
+        PlayerColor::Black => 1,
+    };
+
+    to / BOARD_SIZE == seventh_rank && state.get_piece_type_color(player_color, from) == PieceType::Pawn
+}
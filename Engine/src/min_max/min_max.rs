@@ -1,123 +1,908 @@
-use std::sync::Arc;
-use std::sync::atomic::{AtomicI64, Ordering};
-use async_recursion::async_recursion;
-use tokio::task;
+use std::cmp::Reverse;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::thread;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
 use crate::chessboard::chessboard::Chessboard;
 use crate::chessboard::player::PlayerColor;
 use crate::evaluator::evaluate;
+use crate::min_max::extensions::{
+    is_passed_pawn_push, MAX_EXTENSIONS, SINGULAR_DEPTH_MARGIN, SINGULAR_MARGIN, SINGULAR_MIN_DEPTH,
+};
+use crate::min_max::move_ordering::{is_quiet, MoveOrderer, MAX_PLY};
 use crate::min_max::next_move_generator::generate_next_moves;
+use crate::min_max::pruning::{
+    has_non_pawn_material, late_move_pruning_count, null_move_reduction, ReductionTable, FUTILITY_DEPTH,
+    FUTILITY_MARGIN, LATE_MOVE_PRUNING_DEPTH, LMR_FULL_DEPTH_MOVES, LMR_MIN_DEPTH, NULL_MOVE_MIN_DEPTH,
+    NULL_MOVE_VERIFICATION_DEPTH, RAZORING_DEPTH, RAZORING_MARGIN, REVERSE_FUTILITY_DEPTH, REVERSE_FUTILITY_MARGIN,
+};
+use crate::min_max::quiescence::quiescence_search;
+use crate::min_max::search_info::SearchInfo;
+use crate::min_max::search_options::SearchOptions;
+use crate::min_max::search_stats::SearchStats;
+use crate::min_max::search_limits::{SearchLimits, StopFlag};
+use crate::min_max::time_manager::{TimeControl, TimeManager};
+use crate::min_max::score::Score;
+use crate::min_max::skill::{is_limited, limit_search, pick_line, SKILL_MULTI_PV};
+use crate::min_max::transposition_table::{Bound, TranspositionTable};
 
-const DEPTH: usize = 4;
+// plies searched when there is no clock to follow
+const DEPTH: usize = 5;
+const MAX_DEPTH: usize = 64;
+// the clock is only looked at every so many nodes
+const TIME_CHECK_INTERVAL: u64 = 1024;
+// plies without a capture or pawn move after which the game is drawn
+pub const FIFTY_MOVE_PLIES: u64 = 100;
 
-#[async_recursion]
-async fn min_max_with_alpha_beta_pruning(
+// one root move with its score and principal variation
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchLine {
+    pub score: Score,
+    pub principal_variation: Vec<(u64, u64)>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchResult {
+    pub best_move: (u64, u64),
+    pub score: Score,
+    pub depth: usize,
+    pub principal_variation: Vec<(u64, u64)>,
+    // the best options.multi_pv root moves, best first; the first one is the result itself
+    pub lines: Vec<SearchLine>,
+    pub stats: SearchStats,
+}
+
+impl SearchResult {
+    fn from_lines(lines: Vec<SearchLine>, depth: usize) -> SearchResult {
+        SearchResult {
+            best_move: lines[0].principal_variation[0],
+            score: lines[0].score,
+            depth,
+            principal_variation: lines[0].principal_variation.clone(),
+            lines,
+            stats: SearchStats::default(),
+        }
+    }
+}
+
+// everything the nodes of one search thread share
+struct SearchContext<'a> {
+    options: &'a SearchOptions,
+    reductions: ReductionTable,
+    transposition_table: &'a TranspositionTable,
+    move_orderer: &'a MoveOrderer,
+    // the hash move left out by a singular extension search at that ply
+    excluded_moves: Vec<Option<(u64, u64)>>,
+    // root moves already found by earlier MultiPV lines of the iteration
+    root_excluded: Vec<(u64, u64)>,
+    // a copy per thread, restarted by each thread when it sees the ponder hit
+    time_manager: TimeManager,
+    // no clock until the caller reports a ponder hit
+    pondering: bool,
+    node_limit: Option<u64>,
+    // raised by whichever thread runs out of time or nodes first, and by the main thread when it is done
+    stop: &'a AtomicBool,
+    // raised by the caller
+    stop_flag: &'a StopFlag,
+    // the nodes of all threads, each adds its own every so often
+    total_nodes: &'a AtomicU64,
+    stats: SearchStats,
+    nodes_reported: u64,
+    // deepest ply reached so far
+    seldepth: usize,
+    stopped: bool,
+    // the best root move of the running iteration, exact once it has been searched
+    root_best: Option<(Score, Vec<(u64, u64)>)>,
+    // the keys of the positions before the root, and of the ones on the way to each ply
+    game_history: &'a [u64],
+    keys: Vec<u64>,
+}
+
+impl<'a> SearchContext<'a> {
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        options: &'a SearchOptions,
+        transposition_table: &'a TranspositionTable,
+        move_orderer: &'a MoveOrderer,
+        time_manager: &TimeManager,
+        node_limit: Option<u64>,
+        stop: &'a AtomicBool,
+        stop_flag: &'a StopFlag,
+        total_nodes: &'a AtomicU64,
+        game_history: &'a [u64]
+    ) -> SearchContext<'a> {
+        SearchContext {
+            options,
+            reductions: ReductionTable::new(options.lmr_base, options.lmr_divisor),
+            transposition_table,
+            move_orderer,
+            excluded_moves: vec![None; MAX_PLY],
+            root_excluded: vec![],
+            time_manager: time_manager.clone(),
+            pondering: stop_flag.is_pondering(),
+            node_limit,
+            stop,
+            stop_flag,
+            total_nodes,
+            stats: SearchStats::default(),
+            nodes_reported: 0,
+            seldepth: 0,
+            stopped: false,
+            root_best: None,
+            game_history,
+            keys: vec![0; MAX_PLY + 1],
+        }
+    }
+
+    fn should_stop(&mut self) -> bool {
+        if self.stopped {
+            return true;
+        }
+
+        // the node limit counts the nodes of all threads, the ones this thread has not
+        // reported yet included, so a single thread stops exactly and reproducibly
+        if let Some(node_limit) = self.node_limit {
+            let searched = self.total_nodes.load(Ordering::Relaxed) + self.stats.total_nodes() - self.nodes_reported;
+            if searched >= node_limit {
+                self.stop.store(true, Ordering::Relaxed);
+            }
+        }
+
+        // the shared counters, the ponder hit and the clock every so many nodes
+        if self.stats.nodes.is_multiple_of(TIME_CHECK_INTERVAL) {
+            self.report_nodes();
+            self.follow_ponder_hit();
+            if self.stop_flag.is_stopped() || (!self.pondering && self.time_manager.is_time_up()) {
+                self.stop.store(true, Ordering::Relaxed);
+            }
+        }
+        self.stopped = self.stop.load(Ordering::Relaxed);
+
+        self.stopped
+    }
+
+    // on a ponder hit the search carries on as a normal one, its clock starting now
+    fn follow_ponder_hit(&mut self) {
+        if self.pondering && !self.stop_flag.is_pondering() {
+            self.pondering = false;
+            self.time_manager.restart();
+        }
+    }
+
+    fn should_start_iteration(&mut self) -> bool {
+        self.follow_ponder_hit();
+        self.pondering || self.time_manager.should_start_iteration()
+    }
+
+    // Whether the position at ply was already reached since the last capture or pawn move,
+    // in the search or in the game before it. A single repetition is scored as a draw.
+    fn is_repetition(&self, key: u64, ply: usize, halfmove_clock: u64) -> bool {
+        // the same side is to move every other ply, and it takes four to get back
+        (4..=halfmove_clock as usize).step_by(2).any(|distance| {
+            let earlier = match distance <= ply {
+                true => Some(self.keys[ply - distance]),
+                false => self.game_history.len().checked_sub(distance - ply).map(|index| self.game_history[index]),
+            };
+            earlier == Some(key)
+        })
+    }
+
+    // Contempt: a draw is worth less than nothing to the side to move at the root, and
+    // as much more to its opponent, so the search avoids draws (or seeks them below zero).
+    fn draw_score(&self, ply: usize) -> Score {
+        match ply % 2 {
+            0 => Score::centipawns(-self.options.contempt),
+            _ => Score::centipawns(self.options.contempt),
+        }
+    }
+
+    fn report_nodes(&mut self) {
+        let nodes = self.stats.total_nodes();
+        self.total_nodes.fetch_add(nodes - self.nodes_reported, Ordering::Relaxed);
+        self.nodes_reported = nodes;
+    }
+}
+
+// Negamax principal variation search: scores are from the side to move's point of view.
+// The first move is searched with the full window, the rest with a null window around
+// alpha and only re-searched when they turn out to be better.
+#[allow(clippy::too_many_arguments)]
+fn principal_variation_search(
+    context: &mut SearchContext,
     state: &Chessboard,
     depth: usize,
-    alpha: i64,
-    beta: i64,
-    player_color: PlayerColor
-) -> i64 {
+    ply: usize,
+    mut alpha: Score,
+    mut beta: Score,
+    player_color: PlayerColor,
+    previous_move: Option<(u64, u64)>,
+    allow_null_move: bool,
+    extensions: usize,
+    principal_variation: &mut Vec<(u64, u64)>
+) -> Score {
+    principal_variation.clear();
+    context.stats.nodes += 1;
+    context.seldepth = context.seldepth.max(ply);
 
-    if depth == 0 || state.is_finished() {
-        return evaluate(&state, player_color);
+    // the iteration is thrown away once the search stops, the value doesn't matter
+    if context.should_stop() {
+        return Score::DRAW;
     }
 
-    let possible_moves = generate_next_moves(&state, player_color);
+    if state.is_finished() {
+        return Score::centipawns(evaluate(state, player_color));
+    }
 
-    match player_color {
-        PlayerColor::White => {
-            let value_atomic = Arc::new(AtomicI64::new(i64::MIN));
-            let alpha_atomic = Arc::new(AtomicI64::new(alpha));
-            let mut joins = vec![];
+    let key = state.zobrist_hash();
+    context.keys[ply] = key;
+    if ply > 0 && (state.halfmove_clock >= FIFTY_MOVE_PLIES || context.is_repetition(key, ply, state.halfmove_clock)) {
+        return context.draw_score(ply);
+    }
 
-            for next_move in possible_moves {
-                let mut next_state = state.clone();
-                let value_clone = value_atomic.clone();
-                let alpha_clone = alpha_atomic.clone();
+    // mate distance pruning: nothing below can beat a mate already found closer to the root
+    if ply > 0 {
+        alpha = alpha.max(Score::mated_in(ply));
+        beta = beta.min(Score::mate_in(ply + 1));
+        if alpha >= beta {
+            return alpha;
+        }
+    }
+
+    if depth == 0 || ply >= MAX_PLY {
+        return quiescence_search(
+            state,
+            alpha,
+            beta,
+            player_color,
+            ply,
+            0,
+            context.options,
+            context.move_orderer,
+            &mut context.stats
+        );
+    }
+
+    // cutoffs on PV nodes would cut the principal variation short
+    let pv_node = beta.value() - alpha.value() > 1;
+    // the entry belongs to the full node, not to the search without the excluded move
+    let excluded_move = context.excluded_moves[ply];
+    let table_entry = match excluded_move {
+        None => {
+            context.stats.tt_probes += 1;
+            context.transposition_table.probe(key, ply)
+        },
+        Some(_) => None,
+    };
+    let mut hash_move = None;
+    if let Some(entry) = table_entry {
+        context.stats.tt_hits += 1;
+        if !pv_node && entry.depth >= depth {
+            match entry.bound {
+                Bound::Exact => return entry.score,
+                Bound::Lower if entry.score >= beta => return entry.score,
+                Bound::Upper if entry.score <= alpha => return entry.score,
+                _ => {}
+            }
+        }
+        hash_move = entry.best_move;
+    }
+
+    // right after a capture or pawn move the endgame tables know the result
+    #[cfg(feature = "syzygy")]
+    if ply > 0 && excluded_move.is_none() {
+        if let Some(score) = context.options.tablebase.as_ref().and_then(|tablebase| tablebase.probe_wdl(state, ply)) {
+            let score = if score == Score::DRAW { context.draw_score(ply) } else { score };
+            context.stats.tb_hits += 1;
+            context.transposition_table.store(key, depth, Bound::Exact, score, None, ply);
+            return score;
+        }
+    }
+
+    let in_check = state.is_in_check(player_color);
+    // no pruning when a mate score is at stake or the side to move has to escape check
+    let prunable = !pv_node && !in_check && ply > 0 && !alpha.is_mate() && !beta.is_mate();
+    let static_eval = if prunable { Score::centipawns(evaluate(state, player_color)) } else { Score::DRAW };
+
+    // reverse futility: far enough above beta that the opponent can't catch up in time
+    if prunable
+        && context.options.reverse_futility_pruning
+        && depth <= REVERSE_FUTILITY_DEPTH
+        && static_eval - REVERSE_FUTILITY_MARGIN * depth as i64 >= beta
+    {
+        return static_eval;
+    }
+
+    // razoring: hopeless positions near the horizon drop into the quiescence search
+    if prunable
+        && context.options.razoring
+        && depth <= RAZORING_DEPTH
+        && static_eval + RAZORING_MARGIN * (depth as i64) < alpha
+    {
+        let value = quiescence_search(
+            state,
+            alpha,
+            alpha + 1,
+            player_color,
+            ply,
+            0,
+            context.options,
+            context.move_orderer,
+            &mut context.stats
+        );
+        if value <= alpha {
+            return value;
+        }
+    }
+
+    // null move: if passing still fails high, a real move will too
+    if prunable
+        && allow_null_move
+        && context.options.null_move_pruning
+        && depth >= NULL_MOVE_MIN_DEPTH
+        && static_eval >= beta
+        && has_non_pawn_material(state, player_color)
+    {
+        let reduction = null_move_reduction(depth).min(depth - 1);
+        let mut null_state = state.clone();
+        null_state.make_null_move();
+        let mut child_variation = vec![];
+
+        let value = -principal_variation_search(
+            context,
+            &null_state,
+            depth - 1 - reduction,
+            ply + 1,
+            -beta,
+            -beta + 1,
+            player_color.opposite(),
+            None,
+            false,
+            extensions,
+            &mut child_variation
+        );
 
-                joins.push(task::spawn(async move {
-                    let from = Chessboard::convert_index_to_square(next_move.0);
-                    let to = Chessboard::convert_index_to_square(next_move.1);
-                    next_state.perform_move(&from, &to, PlayerColor::White).unwrap();
+        if context.stopped {
+            return Score::DRAW;
+        }
 
-                    if value_clone.load(Ordering::Relaxed) < beta {
-                        let result = min_max_with_alpha_beta_pruning(
-                            &next_state,
-                            depth - 1,
-                            alpha_clone.load(Ordering::Relaxed),
-                            beta,
-                            PlayerColor::Black
-                        ).await;
+        if value >= beta {
+            if depth < NULL_MOVE_VERIFICATION_DEPTH {
+                context.stats.null_move_cutoffs += 1;
+                return beta;
+            }
 
-                        value_clone.fetch_max(result, Ordering::Relaxed);
+            // verify at reduced depth without null moves, against zugzwang
+            let verification = principal_variation_search(
+                context,
+                state,
+                depth - reduction,
+                ply,
+                beta - 1,
+                beta,
+                player_color,
+                previous_move,
+                false,
+                extensions,
+                &mut child_variation
+            );
 
-                        alpha_clone.fetch_max(result, Ordering::Relaxed);
-                    }
-                }));
+            if context.stopped {
+                return Score::DRAW;
             }
-            for join in joins {
-                join.await.unwrap();
+
+            if verification >= beta {
+                context.stats.null_move_cutoffs += 1;
+                return beta;
             }
+        }
+    }
+
+    let mut possible_moves = generate_next_moves(state, player_color);
+
+    if possible_moves.is_empty() {
+        if !in_check {
+            return context.draw_score(ply);
+        }
+
+        // mated: prefer the shortest mate, delay our own as long as possible
+        return Score::mated_in(ply);
+    }
 
-            value_atomic.load(Ordering::Relaxed)
+    context.move_orderer.order_moves(state, &mut possible_moves, player_color, hash_move, ply, previous_move);
+
+    let mut child_variation = vec![];
+
+    // singular extension: the hash move is extended when every other move, searched
+    // shallower, stays clearly below its score
+    let singular_move = match table_entry {
+        Some(entry) if context.options.singular_extensions
+            && ply > 0
+            && depth >= SINGULAR_MIN_DEPTH
+            && extensions < MAX_EXTENSIONS
+            && entry.best_move.is_some()
+            && entry.bound != Bound::Upper
+            && entry.depth + SINGULAR_DEPTH_MARGIN >= depth
+            && !entry.score.is_mate() =>
+        {
+            let singular_beta = entry.score - SINGULAR_MARGIN * depth as i64;
+            context.excluded_moves[ply] = entry.best_move;
+            let value = principal_variation_search(
+                context,
+                state,
+                (depth - 1) / 2,
+                ply,
+                singular_beta - 1,
+                singular_beta,
+                player_color,
+                previous_move,
+                false,
+                extensions,
+                &mut child_variation
+            );
+            context.excluded_moves[ply] = None;
+
+            if context.stopped {
+                return Score::DRAW;
+            }
+
+            if value < singular_beta { entry.best_move } else { None }
         },
-        PlayerColor::Black => {
-            let value_atomic = Arc::new(AtomicI64::new(i64::MAX));
-            let beta_atomic = Arc::new(AtomicI64::new(beta));
-            let mut joins = vec![];
+        _ => None,
+    };
+
+    let original_alpha = alpha;
+    let mut best_value = -Score::INFINITY;
+    let mut best_move = None;
+    let mut moves_searched = 0;
+    // the quiet moves searched before a cutoff, to lower their history
+    let mut quiets_tried: Vec<(u64, u64)> = vec![];
+
+    for (move_number, next_move) in possible_moves.iter().copied().enumerate() {
+        if excluded_move == Some(next_move) || (ply == 0 && context.root_excluded.contains(&next_move)) {
+            continue;
+        }
+
+        let quiet = is_quiet(state, next_move, player_color);
+        let mut next_state = state.clone();
+        let from = Chessboard::convert_index_to_square(next_move.0);
+        let to = Chessboard::convert_index_to_square(next_move.1);
+        next_state.perform_move(&from, &to, player_color).unwrap();
+        let gives_check = next_state.is_in_check(player_color.opposite());
+
+        // forcing moves are searched a ply deeper, up to a limit per line
+        let extend = extensions < MAX_EXTENSIONS
+            && ((context.options.check_extensions && gives_check)
+                || singular_move == Some(next_move)
+                || (context.options.passed_pawn_extensions && is_passed_pawn_push(state, next_move, player_color)));
+        let extension = usize::from(extend);
+        let new_depth = depth - 1 + extension;
+
+        // the first move is always searched so there is a score to return
+        if prunable && moves_searched > 0 && quiet && !gives_check && !extend {
+            if context.options.late_move_pruning
+                && depth <= LATE_MOVE_PRUNING_DEPTH
+                && quiets_tried.len() >= late_move_pruning_count(depth)
+            {
+                continue;
+            }
+
+            if context.options.futility_pruning
+                && depth <= FUTILITY_DEPTH
+                && static_eval + FUTILITY_MARGIN * depth as i64 <= alpha
+            {
+                continue;
+            }
+        }
+
+        let first_move = moves_searched == 0;
+        moves_searched += 1;
 
-            for next_move in possible_moves {
-                let mut next_state = state.clone();
-                let value_clone = value_atomic.clone();
-                let beta_clone = beta_atomic.clone();
+        let mut value = if first_move {
+            -Score::INFINITY
+        } else {
+            // late quiet moves are searched shallower first
+            let reduction = if context.options.late_move_reductions
+                && depth >= LMR_MIN_DEPTH
+                && moves_searched > LMR_FULL_DEPTH_MOVES
+                && quiet
+                && !in_check
+                && !extend
+            {
+                context.reductions.reduction(depth, move_number)
+            } else {
+                0
+            };
 
-                joins.push(task::spawn(async move {
-                    let from = Chessboard::convert_index_to_square(next_move.0);
-                    let to = Chessboard::convert_index_to_square(next_move.1);
-                    next_state.perform_move(&from, &to, PlayerColor::Black).unwrap();
+            let mut value = -principal_variation_search(
+                context,
+                &next_state,
+                new_depth - reduction,
+                ply + 1,
+                -alpha - 1,
+                -alpha,
+                player_color.opposite(),
+                Some(next_move),
+                true,
+                extensions + extension,
+                &mut child_variation
+            );
 
-                    if value_clone.load(Ordering::Relaxed) > alpha {
-                        let result = min_max_with_alpha_beta_pruning(
-                            &next_state,
-                            depth - 1,
-                            alpha,
-                            beta_clone.load(Ordering::Relaxed),
-                            PlayerColor::White
-                        ).await;
+            if reduction > 0 && value > alpha {
+                context.stats.lmr_researches += 1;
+                value = -principal_variation_search(
+                    context,
+                    &next_state,
+                    new_depth,
+                    ply + 1,
+                    -alpha - 1,
+                    -alpha,
+                    player_color.opposite(),
+                    Some(next_move),
+                    true,
+                    extensions + extension,
+                    &mut child_variation
+                );
+            }
+
+            value
+        };
+
+        if first_move || (value > alpha && value < beta) {
+            value = -principal_variation_search(
+                context,
+                &next_state,
+                new_depth,
+                ply + 1,
+                -beta,
+                -alpha,
+                player_color.opposite(),
+                Some(next_move),
+                true,
+                extensions + extension,
+                &mut child_variation
+            );
+        }
+
+        if context.stopped {
+            return Score::DRAW;
+        }
+
+        if value > best_value {
+            best_value = value;
+            best_move = Some(next_move);
 
-                        value_clone.fetch_min(result, Ordering::Relaxed);
+            if value > alpha {
+                alpha = value;
+                principal_variation.clear();
+                principal_variation.push(next_move);
+                principal_variation.append(&mut child_variation);
 
-                        beta_clone.fetch_min(result, Ordering::Relaxed);
-                    }
-                }));
+                if ply == 0 {
+                    context.root_best = Some((value, principal_variation.clone()));
+                }
             }
-            for join in joins {
-                join.await.unwrap();
+        }
+
+        if alpha >= beta {
+            context.stats.beta_cutoffs += 1;
+            if first_move {
+                context.stats.first_move_cutoffs += 1;
             }
+            context.move_orderer.record_cutoff(
+                state,
+                player_color,
+                next_move,
+                move_number,
+                &quiets_tried,
+                depth,
+                ply,
+                previous_move,
+            );
+            break;
+        }
 
-            value_atomic.load(Ordering::Relaxed)
+        if quiet {
+            quiets_tried.push(next_move);
         }
     }
+
+    let bound = if best_value <= original_alpha {
+        Bound::Upper
+    } else if best_value >= beta {
+        Bound::Lower
+    } else {
+        Bound::Exact
+    };
+    if excluded_move.is_none() && (ply > 0 || context.root_excluded.is_empty()) {
+        context.transposition_table.store(key, depth, bound, best_value, best_move, ply);
+    }
+
+    best_value
 }
 
-pub async fn get_best_move(state: &Chessboard) -> (u64, u64) {
-    let mut result = (0, 0);
-    let possible_moves = generate_next_moves(&state, PlayerColor::White);
-    let mut value = i64::MIN;
-    for next_move in possible_moves {
-        let mut next_state = state.clone();
+// Lazy SMP: every thread runs its own iterative deepening over the shared transposition
+// table, helpers with their own move ordering tables and staggered depths, so they fill
+// the table with results the main thread picks up. The main thread's result is returned,
+// when the limits are reached or the caller raises the stop flag.
+pub fn search(
+    state: &Chessboard,
+    limits: &SearchLimits,
+    options: &SearchOptions,
+    transposition_table: &TranspositionTable,
+    move_orderer: &MoveOrderer,
+    stop_flag: &StopFlag,
+    on_info: Option<&dyn Fn(&SearchInfo)>
+) -> SearchResult {
+    // the side to move is whoever the board says it is
+    let time_control = match options.deterministic {
+        true => TimeControl::default(),
+        false => limits.time_control(state.side_to_move),
+    };
+    let time_manager = TimeManager::new(&time_control);
+    let mut max_depth = match limits.depth {
+        Some(depth) => depth.clamp(1, MAX_DEPTH),
+        None if limits.is_limited() => MAX_DEPTH,
+        None => DEPTH,
+    };
+    transposition_table.new_search();
+    move_orderer.new_search();
 
-        let from = Chessboard::convert_index_to_square(next_move.0);
-        let to = Chessboard::convert_index_to_square(next_move.1);
-        next_state.perform_move(&from, &to, PlayerColor::White).unwrap();
+    let possible_moves = generate_next_moves(state, state.side_to_move);
+    let result = SearchResult {
+        best_move: possible_moves.first().copied().unwrap_or((0, 0)),
+        score: Score::DRAW,
+        depth: 0,
+        principal_variation: vec![],
+        lines: vec![],
+        stats: SearchStats::default(),
+    };
+    // nothing to think about, unless asked to keep going
+    if possible_moves.is_empty() && !limits.infinite {
+        return result;
+    }
+    // a single move still gets one ply, for its score, line and report
+    if possible_moves.len() == 1 && !limits.infinite {
+        max_depth = 1;
+    }
 
-        let min_max_value = min_max_with_alpha_beta_pruning(&next_state, DEPTH, i64::MIN, i64::MAX, PlayerColor::Black).await;
+    // the tables know the best move of positions with few enough pieces
+    #[cfg(feature = "syzygy")]
+    if let Some((best_move, score)) = options.tablebase.as_ref().and_then(|tablebase| tablebase.probe_root(state)) {
+        if possible_moves.contains(&best_move) {
+            let mut result = SearchResult::from_lines(vec![SearchLine { score, principal_variation: vec![best_move] }], 0);
+            result.stats.tb_hits = 1;
+            return result;
+        }
+    }
+
+    let stop = AtomicBool::new(false);
+    let total_nodes = AtomicU64::new(0);
+    thread::scope(|scope| {
+        let helpers: Vec<_> = (1..options.search_threads()).map(|thread_index| {
+            let stop = &stop;
+            let time_manager = &time_manager;
+            let total_nodes = &total_nodes;
+            let fallback = result.clone();
+            scope.spawn(move || {
+                let move_orderer = MoveOrderer::new();
+                let context = SearchContext::new(
+                    options,
+                    transposition_table,
+                    &move_orderer,
+                    time_manager,
+                    limits.nodes,
+                    stop,
+                    stop_flag,
+                    total_nodes,
+                    &limits.game_history,
+                );
+                iterative_deepening(context, state, time_manager, limits, max_depth, thread_index, fallback, None)
+            })
+        }).collect();
+
+        let context = SearchContext::new(
+            options,
+            transposition_table,
+            move_orderer,
+            &time_manager,
+            limits.nodes,
+            &stop,
+            stop_flag,
+            &total_nodes,
+            &limits.game_history,
+        );
+        let mut result = iterative_deepening(context, state, &time_manager, limits, max_depth, 0, result, on_info);
+        stop.store(true, Ordering::Relaxed);
+
+        for helper in helpers {
+            let helper_result = helper.join().unwrap();
+            result.stats.merge(&helper_result.stats);
+        }
+
+        result
+    })
+}
+
+// Searches one ply deeper each iteration, the previous best move first through the
+// transposition table. A stopped iteration still counts for the root moves it finished.
+// Every other helper thread starts a ply deeper. Only the main thread reports its progress.
+// With MultiPV each iteration searches the root again for every line, leaving out the
+// moves the lines before it already found.
+#[allow(clippy::too_many_arguments)]
+fn iterative_deepening(
+    mut context: SearchContext,
+    state: &Chessboard,
+    time_manager: &TimeManager,
+    limits: &SearchLimits,
+    max_depth: usize,
+    thread_index: usize,
+    mut result: SearchResult,
+    on_info: Option<&dyn Fn(&SearchInfo)>
+) -> SearchResult {
+    // helpers only help with the best move
+    let multi_pv = match thread_index {
+        0 => context.options.multi_pv.clamp(1, generate_next_moves(state, state.side_to_move).len().max(1)),
+        _ => 1,
+    };
 
-        if min_max_value > value {
-            value = min_max_value;
-            result = next_move;
+    for depth in (1 + thread_index % 2)..=max_depth {
+        if depth > 1 && !limits.infinite && !context.should_start_iteration() {
+            break;
+        }
+
+        let iteration_start = (context.stats.total_nodes(), Instant::now());
+        let mut lines = vec![];
+        context.root_excluded.clear();
+        while lines.len() < multi_pv {
+            context.root_best = None;
+            let mut principal_variation = vec![];
+            let score = principal_variation_search(
+                &mut context,
+                state,
+                depth,
+                0,
+                -Score::INFINITY,
+                Score::INFINITY,
+                state.side_to_move,
+                None,
+                true,
+                0,
+                &mut principal_variation
+            );
+
+            if context.stopped {
+                if let Some((score, principal_variation)) = context.root_best.take() {
+                    lines.push(SearchLine { score, principal_variation });
+                }
+                break;
+            }
+
+            if principal_variation.is_empty() {
+                break;
+            }
+
+            context.root_excluded.push(principal_variation[0]);
+            lines.push(SearchLine { score, principal_variation });
+        }
+        context.root_excluded.clear();
+
+        if context.stopped {
+            // the lines of the unfinished iteration first, then the rest of the previous one
+            if !lines.is_empty() {
+                let found: Vec<(u64, u64)> = lines.iter().map(|line| line.principal_variation[0]).collect();
+                lines.extend(
+                    result.lines
+                        .iter()
+                        .filter(|line| !found.contains(&line.principal_variation[0]))
+                        .cloned()
+                );
+                lines.truncate(multi_pv);
+                result = SearchResult::from_lines(lines, result.depth);
+            }
+            break;
+        }
+
+        if lines.is_empty() {
+            break;
+        }
+
+        // a later line can come out above an earlier one when the search is unstable
+        lines.sort_by_key(|line| Reverse(line.score));
+        result = SearchResult::from_lines(lines, depth);
+        context.stats.end_iteration(depth, iteration_start.0, iteration_start.1.elapsed());
+
+        if let Some(on_info) = on_info {
+            context.report_nodes();
+            let nodes = context.total_nodes.load(Ordering::Relaxed);
+            let time = time_manager.elapsed();
+            for (index, line) in result.lines.iter().enumerate() {
+                on_info(&SearchInfo {
+                    depth,
+                    seldepth: context.seldepth,
+                    multipv: index + 1,
+                    score: line.score,
+                    nodes,
+                    nps: SearchInfo::nodes_per_second(nodes, time),
+                    time,
+                    hashfull: context.transposition_table.hashfull(),
+                    principal_variation: line.principal_variation.clone(),
+                });
+            }
+        }
+
+        if let (Some(mate), Some(moves)) = (limits.mate, result.score.mate_moves()) {
+            if moves > 0 && moves <= mate as i64 {
+                break;
+            }
         }
     }
 
+    result.stats = context.stats;
     result
-}
\ No newline at end of file
+}
+
+// The random choices of a position, the same every time with a seed or a deterministic
+// search, so games against weak levels and with a book can be played again.
+fn random_seed(state: &Chessboard, options: &SearchOptions) -> u64 {
+    match (options.seed, options.deterministic) {
+        (Some(seed), _) => state.zobrist_hash() ^ seed,
+        (None, true) => state.zobrist_hash(),
+        (None, false) => state.zobrist_hash() ^ SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |time| time.as_nanos() as u64),
+    }
+}
+
+// a move from the opening book, when one is set and knows the position
+pub fn book_move(state: &Chessboard, options: &SearchOptions) -> Option<(u64, u64)> {
+    let book = options.book.as_ref()?;
+    book.choose_move(state, options.book_selection, random_seed(state, options))
+}
+
+// The search at the skill level of the options. A limited level searches less and more
+// lines on a single thread, then settles for one of them, the result's best move, score
+// and principal variation becoming that line's.
+pub fn search_with_skill(
+    state: &Chessboard,
+    limits: &SearchLimits,
+    options: &SearchOptions,
+    transposition_table: &TranspositionTable,
+    move_orderer: &MoveOrderer,
+    stop_flag: &StopFlag,
+    on_info: Option<&dyn Fn(&SearchInfo)>
+) -> SearchResult {
+    if !is_limited(options.skill_level) {
+        return search(state, limits, options, transposition_table, move_orderer, stop_flag, on_info);
+    }
+
+    let skill_limits = limit_search(limits, options.skill_level);
+    let skill_options = SearchOptions {
+        multi_pv: options.multi_pv.max(SKILL_MULTI_PV),
+        threads: 1,
+        ..options.clone()
+    };
+    let mut result = search(state, &skill_limits, &skill_options, transposition_table, move_orderer, stop_flag, on_info);
+    if result.lines.is_empty() {
+        return result;
+    }
+
+    let line = pick_line(&result.lines, options.skill_level, random_seed(state, options)).clone();
+    result.best_move = line.principal_variation[0];
+    result.score = line.score;
+    result.principal_variation = line.principal_variation;
+    result
+}
+
+// the book move if there is one, the move the search settles for at the skill level otherwise
+pub fn get_best_move(
+    state: &Chessboard,
+    limits: &SearchLimits,
+    options: &SearchOptions,
+    transposition_table: &TranspositionTable,
+    move_orderer: &MoveOrderer,
+    stop_flag: &StopFlag,
+    on_info: Option<&dyn Fn(&SearchInfo)>
+) -> (u64, u64) {
+    if let Some(next_move) = book_move(state, options) {
+        return next_move;
+    }
+
+    search_with_skill(state, limits, options, transposition_table, move_orderer, stop_flag, on_info).best_move
+}
@@ -1,2 +1,17 @@
 pub(crate) mod next_move_generator;
-pub(crate) mod min_max;
\ No newline at end of file
+pub(crate) mod min_max;
+pub(crate) mod time_manager;
+pub(crate) mod transposition_table;
+pub(crate) mod move_ordering;
+pub(crate) mod quiescence;
+pub(crate) mod search_options;
+pub(crate) mod pruning;
+pub(crate) mod extensions;
+pub(crate) mod score;
+pub(crate) mod benchmark;
+pub(crate) mod search_limits;
+pub(crate) mod search_info;
+pub(crate) mod search_stats;
+#[cfg(feature = "syzygy")]
+pub(crate) mod syzygy;
+pub(crate) mod skill;
\ No newline at end of file
@@ -0,0 +1,228 @@
+use std::sync::atomic::{AtomicI32, AtomicU16, AtomicU64, Ordering};
+use crate::chessboard::chessboard::Chessboard;
+use crate::chessboard::piece::PieceType;
+use crate::chessboard::player::PlayerColor;
+use crate::constants::{BOARD_SIZE, NUM_SQUARES};
+
+pub const MAX_PLY: usize = 128;
+
+// ordering bands, from searched first to searched last
+const HASH_MOVE_SCORE: i64 = 1_000_000_000;
+const CAPTURE_SCORE: i64 = 100_000_000;
+const FIRST_KILLER_SCORE: i64 = 90_000_000;
+const SECOND_KILLER_SCORE: i64 = 89_000_000;
+const COUNTERMOVE_SCORE: i64 = 80_000_000;
+// captures that lose material on the exchange come after the quiet moves
+const LOSING_CAPTURE_SCORE: i64 = -100_000_000;
+// quiet moves are ordered by their history score, which stays within +-HISTORY_MAX
+const HISTORY_MAX: i32 = 1_000_000;
+
+const NUM_MOVES: usize = (NUM_SQUARES * NUM_SQUARES) as usize;
+const NO_MOVE: u16 = 0;
+
+// Killers, countermoves and history are shared by all tasks of a search,
+// so they are kept in relaxed atomics: a lost update only costs ordering quality.
+pub struct MoveOrderer {
+    // two killer moves per ply
+    killers: Vec<AtomicU16>,
+    // indexed by the opponent's previous move
+    countermoves: Vec<AtomicU16>,
+    // indexed by side to move, from and to
+    history: Vec<AtomicI32>,
+    cutoffs: AtomicU64,
+    first_move_cutoffs: AtomicU64,
+}
+
+impl MoveOrderer {
+    pub fn new() -> MoveOrderer {
+        MoveOrderer {
+            killers: (0..2 * MAX_PLY).map(|_| AtomicU16::new(NO_MOVE)).collect(),
+            countermoves: (0..NUM_MOVES).map(|_| AtomicU16::new(NO_MOVE)).collect(),
+            history: (0..2 * NUM_MOVES).map(|_| AtomicI32::new(0)).collect(),
+            cutoffs: AtomicU64::new(0),
+            first_move_cutoffs: AtomicU64::new(0),
+        }
+    }
+
+    // killers and statistics are per search, history fades out slowly
+    pub fn new_search(&self) {
+        for killer in &self.killers {
+            killer.store(NO_MOVE, Ordering::Relaxed);
+        }
+        for entry in &self.history {
+            entry.store(entry.load(Ordering::Relaxed) / 2, Ordering::Relaxed);
+        }
+        self.cutoffs.store(0, Ordering::Relaxed);
+        self.first_move_cutoffs.store(0, Ordering::Relaxed);
+    }
+
+    pub fn order_moves(
+        &self,
+        state: &Chessboard,
+        moves: &mut [(u64, u64)],
+        player_color: PlayerColor,
+        hash_move: Option<(u64, u64)>,
+        ply: usize,
+        previous_move: Option<(u64, u64)>,
+    ) {
+        let ply = ply.min(MAX_PLY - 1);
+        let killers = [
+            decode_move(self.killers[2 * ply].load(Ordering::Relaxed)),
+            decode_move(self.killers[2 * ply + 1].load(Ordering::Relaxed)),
+        ];
+        let countermove = previous_move
+            .and_then(|previous| decode_move(self.countermoves[move_index(previous)].load(Ordering::Relaxed)));
+
+        moves.sort_by_cached_key(|next_move| {
+            let score = if Some(*next_move) == hash_move {
+                HASH_MOVE_SCORE
+            } else if let Some(mvv_lva) = capture_score(state, *next_move, player_color) {
+                if is_promotion(state, *next_move, player_color) || state.see_at_least(*next_move, 0) {
+                    CAPTURE_SCORE + mvv_lva
+                } else {
+                    LOSING_CAPTURE_SCORE + mvv_lva
+                }
+            } else if Some(*next_move) == killers[0] {
+                FIRST_KILLER_SCORE
+            } else if Some(*next_move) == killers[1] {
+                SECOND_KILLER_SCORE
+            } else if Some(*next_move) == countermove {
+                COUNTERMOVE_SCORE
+            } else {
+                self.history[history_index(player_color, *next_move)].load(Ordering::Relaxed) as i64
+            };
+
+            -score
+        });
+    }
+
+    // `quiets_tried` are the quiet moves searched before `cutoff_move` at this node
+    #[allow(clippy::too_many_arguments)]
+    pub fn record_cutoff(
+        &self,
+        state: &Chessboard,
+        player_color: PlayerColor,
+        cutoff_move: (u64, u64),
+        move_number: usize,
+        quiets_tried: &[(u64, u64)],
+        depth: usize,
+        ply: usize,
+        previous_move: Option<(u64, u64)>,
+    ) {
+        self.cutoffs.fetch_add(1, Ordering::Relaxed);
+        if move_number == 0 {
+            self.first_move_cutoffs.fetch_add(1, Ordering::Relaxed);
+        }
+
+        if !is_quiet(state, cutoff_move, player_color) {
+            return;
+        }
+
+        let ply = ply.min(MAX_PLY - 1);
+        let encoded = encode_move(cutoff_move);
+        if self.killers[2 * ply].load(Ordering::Relaxed) != encoded {
+            let first = self.killers[2 * ply].swap(encoded, Ordering::Relaxed);
+            self.killers[2 * ply + 1].store(first, Ordering::Relaxed);
+        }
+
+        if let Some(previous) = previous_move {
+            self.countermoves[move_index(previous)].store(encoded, Ordering::Relaxed);
+        }
+
+        let bonus = (depth * depth).min(400) as i32 * 32;
+        self.update_history(player_color, cutoff_move, bonus);
+        for quiet in quiets_tried {
+            self.update_history(player_color, *quiet, -bonus);
+        }
+    }
+
+    pub fn cutoffs(&self) -> u64 {
+        self.cutoffs.load(Ordering::Relaxed)
+    }
+
+    // share of beta cutoffs produced by the first move searched, in percent
+    pub fn first_move_cutoff_rate(&self) -> f64 {
+        let cutoffs = self.cutoffs();
+        if cutoffs == 0 {
+            return 0.0;
+        }
+
+        self.first_move_cutoffs.load(Ordering::Relaxed) as f64 * 100.0 / cutoffs as f64
+    }
+
+    // history gravity: entries saturate towards +-HISTORY_MAX instead of overflowing
+    fn update_history(&self, player_color: PlayerColor, next_move: (u64, u64), bonus: i32) {
+        let entry = &self.history[history_index(player_color, next_move)];
+        let old = entry.load(Ordering::Relaxed);
+        let new = old + bonus - (old as i64 * bonus.abs() as i64 / HISTORY_MAX as i64) as i32;
+        entry.store(new.clamp(-HISTORY_MAX, HISTORY_MAX), Ordering::Relaxed);
+    }
+}
+
+pub fn is_quiet(state: &Chessboard, next_move: (u64, u64), player_color: PlayerColor) -> bool {
+    capture_score(state, next_move, player_color).is_none()
+}
+
+pub fn is_promotion(state: &Chessboard, next_move: (u64, u64), player_color: PlayerColor) -> bool {
+    let rank = next_move.1 / BOARD_SIZE;
+    state.get_piece_type_color(player_color, next_move.0) == PieceType::Pawn && (rank == 0 || rank == BOARD_SIZE - 1)
+}
+
+// most valuable victim, least valuable attacker; promotions count as capturing a queen
+fn capture_score(state: &Chessboard, next_move: (u64, u64), player_color: PlayerColor) -> Option<i64> {
+    let (from, to) = next_move;
+    let attacker = state.get_piece_type_color(player_color, from);
+    let mut victim = state.get_piece_type_color(player_color.opposite(), to);
+
+    if attacker == PieceType::Pawn {
+        if victim == PieceType::None && from % BOARD_SIZE != to % BOARD_SIZE {
+            // en passant
+            victim = PieceType::Pawn;
+        }
+        if to / BOARD_SIZE == 0 || to / BOARD_SIZE == BOARD_SIZE - 1 {
+            return Some(ordering_value(PieceType::Queen) * 10 + ordering_value(victim) * 10);
+        }
+    }
+
+    if victim == PieceType::None {
+        return None;
+    }
+
+    Some(ordering_value(victim) * 10 - ordering_value(attacker))
+}
+
+fn ordering_value(piece_type: PieceType) -> i64 {
+    match piece_type {
+        PieceType::None => 0,
+        PieceType::Pawn => 1,
+        PieceType::Knight => 2,
+        PieceType::Bishop => 3,
+        PieceType::Rook => 4,
+        PieceType::Queen => 5,
+        PieceType::King => 6,
+    }
+}
+
+fn move_index(next_move: (u64, u64)) -> usize {
+    (next_move.0 * NUM_SQUARES + next_move.1) as usize
+}
+
+fn history_index(player_color: PlayerColor, next_move: (u64, u64)) -> usize {
+    match player_color {
+        PlayerColor::White => move_index(next_move),
+        PlayerColor::Black => NUM_MOVES + move_index(next_move),
+    }
+}
+
+fn encode_move(next_move: (u64, u64)) -> u16 {
+    move_index(next_move) as u16 + 1
+}
+
+fn decode_move(encoded: u16) -> Option<(u64, u64)> {
+    if encoded == NO_MOVE {
+        return None;
+    }
+
+    let index = (encoded - 1) as u64;
+    Some((index / NUM_SQUARES, index % NUM_SQUARES))
+}
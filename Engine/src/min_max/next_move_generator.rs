@@ -1,27 +1,66 @@
 use crate::chessboard::chessboard::Chessboard;
 use crate::chessboard::player::PlayerColor;
 use crate::chessboard::piece;
+use crate::min_max::move_ordering::is_quiet;
+
+const WHITE_PROMOTION_RANK: u64 = 0xFF00_0000_0000_0000;
+const BLACK_PROMOTION_RANK: u64 = 0xFF;
 
 pub fn generate_next_moves(chessboard: &Chessboard, player_color: PlayerColor) -> Vec<(u64, u64)> {
     match player_color {
-        PlayerColor::White => generate_white_moves(chessboard),
-        PlayerColor::Black => generate_black_moves(chessboard)
+        PlayerColor::White => generate_white_moves(chessboard, u64::MAX),
+        PlayerColor::Black => generate_black_moves(chessboard, u64::MAX)
     }
 }
 
+// Captures and promotions for the quiescence search, plus quiet checks if asked for.
+pub fn generate_capture_moves(
+    chessboard: &Chessboard,
+    player_color: PlayerColor,
+    include_checks: bool
+) -> Vec<(u64, u64)> {
+    let en_passant = chessboard.en_passant.map_or(0, |square| 1 << square);
+    let targets = match player_color {
+        PlayerColor::White => chessboard.get_black_board() | WHITE_PROMOTION_RANK | en_passant,
+        PlayerColor::Black => chessboard.get_white_board() | BLACK_PROMOTION_RANK | en_passant,
+    };
+
+    let moves = match (player_color, include_checks) {
+        (_, true) => generate_next_moves(chessboard, player_color),
+        (PlayerColor::White, false) => generate_white_moves(chessboard, targets),
+        (PlayerColor::Black, false) => generate_black_moves(chessboard, targets),
+    };
+
+    moves
+        .into_iter()
+        .filter(|next_move| {
+            !is_quiet(chessboard, *next_move, player_color)
+                || (include_checks && gives_check(chessboard, *next_move, player_color))
+        })
+        .collect()
+}
+
+pub fn gives_check(chessboard: &Chessboard, next_move: (u64, u64), player_color: PlayerColor) -> bool {
+    let mut next_state = chessboard.clone();
+    let from = Chessboard::convert_index_to_square(next_move.0);
+    let to = Chessboard::convert_index_to_square(next_move.1);
+
+    next_state.perform_move(&from, &to, player_color).is_ok() && next_state.is_in_check(player_color.opposite())
+}
+
 // white on bottom, black on top
-fn generate_white_moves(chessboard: &Chessboard) -> Vec<(u64, u64)> {
+fn generate_white_moves(chessboard: &Chessboard, targets: u64) -> Vec<(u64, u64)> {
     let mut result = vec![];
 
     for bit in 0..64 {
         let potential_move = if chessboard.white.pawns.get_square(bit) {
-            piece::get_pawn_moves(bit, PlayerColor::White)
+            piece::get_pawn_moves(bit, PlayerColor::White) | get_pawn_double_push(bit, PlayerColor::White)
         }
         else if chessboard.white.bishops.get_square(bit) {
             piece::get_bishop_moves(bit, chessboard.get_board())
         }
         else if chessboard.white.king.get_square(bit) {
-            piece::get_king_moves(bit)
+            piece::get_king_moves(bit) | get_castling_moves(bit, PlayerColor::White)
         }
         else if chessboard.white.queen.get_square(bit) {
             piece::get_queen_moves(bit, chessboard.get_board())
@@ -36,24 +75,24 @@ fn generate_white_moves(chessboard: &Chessboard) -> Vec<(u64, u64)> {
             0
         };
 
-        add_moves(bit, potential_move, chessboard, PlayerColor::White, &mut result);
+        add_moves(bit, potential_move & targets, chessboard, PlayerColor::White, &mut result);
     }
 
     result
 }
 
-fn generate_black_moves(chessboard: &Chessboard) -> Vec<(u64, u64)> {
+fn generate_black_moves(chessboard: &Chessboard, targets: u64) -> Vec<(u64, u64)> {
     let mut result = vec![];
 
     for bit in 0..64 {
         let potential_move = if chessboard.black.pawns.get_square(bit) {
-            piece::get_pawn_moves(bit, PlayerColor::Black)
+            piece::get_pawn_moves(bit, PlayerColor::Black) | get_pawn_double_push(bit, PlayerColor::Black)
         }
         else if chessboard.black.bishops.get_square(bit) {
             piece::get_bishop_moves(bit, chessboard.get_board())
         }
         else if chessboard.black.king.get_square(bit) {
-            piece::get_king_moves(bit)
+            piece::get_king_moves(bit) | get_castling_moves(bit, PlayerColor::Black)
         }
         else if chessboard.black.queen.get_square(bit) {
             piece::get_queen_moves(bit, chessboard.get_board())
@@ -68,12 +107,29 @@ fn generate_black_moves(chessboard: &Chessboard) -> Vec<(u64, u64)> {
             0
         };
 
-        add_moves(bit, potential_move, chessboard, PlayerColor::Black, &mut result);
+        add_moves(bit, potential_move & targets, chessboard, PlayerColor::Black, &mut result);
     }
 
     result
 }
 
+// double pushes and castling are not part of the piece move masks
+fn get_pawn_double_push(bit: u64, player_color: PlayerColor) -> u64 {
+    match player_color {
+        PlayerColor::White if bit / 8 == 1 => 1 << (bit + 16),
+        PlayerColor::Black if bit / 8 == 6 => 1 << (bit - 16),
+        _ => 0,
+    }
+}
+
+fn get_castling_moves(bit: u64, player_color: PlayerColor) -> u64 {
+    match player_color {
+        PlayerColor::White if bit == 4 => (1 << 2) | (1 << 6),
+        PlayerColor::Black if bit == 60 => (1 << 58) | (1 << 62),
+        _ => 0,
+    }
+}
+
 fn add_moves(
     original: u64,
     new_position_mask: u64,
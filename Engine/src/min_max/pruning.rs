@@ -0,0 +1,65 @@
+use crate::chessboard::chessboard::Chessboard;
+use crate::chessboard::player::PlayerColor;
+use crate::evaluator::PAWN_VALUE;
+use crate::min_max::move_ordering::MAX_PLY;
+
+pub const NULL_MOVE_MIN_DEPTH: usize = 3;
+// null-move cutoffs this deep are confirmed by a reduced search without null moves
+pub const NULL_MOVE_VERIFICATION_DEPTH: usize = 6;
+pub const REVERSE_FUTILITY_DEPTH: usize = 4;
+pub const REVERSE_FUTILITY_MARGIN: i64 = PAWN_VALUE;
+pub const FUTILITY_DEPTH: usize = 3;
+pub const FUTILITY_MARGIN: i64 = 2 * PAWN_VALUE;
+pub const RAZORING_DEPTH: usize = 2;
+pub const RAZORING_MARGIN: i64 = 3 * PAWN_VALUE;
+pub const LATE_MOVE_PRUNING_DEPTH: usize = 3;
+pub const LMR_MIN_DEPTH: usize = 3;
+// moves searched at full depth before reductions start
+pub const LMR_FULL_DEPTH_MOVES: usize = 3;
+
+const MAX_MOVES: usize = 64;
+
+pub fn null_move_reduction(depth: usize) -> usize {
+    2 + depth / 4
+}
+
+// quiet moves searched before the rest are dropped at shallow depth
+pub fn late_move_pruning_count(depth: usize) -> usize {
+    3 + depth * depth
+}
+
+// Null-move pruning is wrong in zugzwang, which mostly happens when the side to move
+// only has pawns left.
+pub fn has_non_pawn_material(state: &Chessboard, player_color: PlayerColor) -> bool {
+    let player = match player_color {
+        PlayerColor::White => &state.white,
+        PlayerColor::Black => &state.black,
+    };
+
+    (player.knights.get_board() | player.bishops.get_board() | player.rooks.get_board() | player.queen.get_board()) != 0
+}
+
+// Late move reductions by depth and move number.
+pub struct ReductionTable {
+    reductions: Vec<[usize; MAX_MOVES]>,
+}
+
+impl ReductionTable {
+    pub fn new(base: f64, divisor: f64) -> ReductionTable {
+        let mut reductions = vec![[0; MAX_MOVES]; MAX_PLY];
+        for (depth, row) in reductions.iter_mut().enumerate().skip(1) {
+            for (move_number, reduction) in row.iter_mut().enumerate().skip(1) {
+                let value = base + (depth as f64).ln() * (move_number as f64).ln() / divisor;
+                *reduction = value.max(0.0) as usize;
+            }
+        }
+
+        ReductionTable { reductions }
+    }
+
+    // never reduces straight into the quiescence search
+    pub fn reduction(&self, depth: usize, move_number: usize) -> usize {
+        let reduction = self.reductions[depth.min(MAX_PLY - 1)][move_number.min(MAX_MOVES - 1)];
+        reduction.min(depth.saturating_sub(2))
+    }
+}
@@ -0,0 +1,101 @@
+use crate::chessboard::chessboard::Chessboard;
+use crate::chessboard::piece::PieceType;
+use crate::chessboard::player::PlayerColor;
+use crate::evaluator::{evaluate, piece_value, PAWN_VALUE};
+use crate::min_max::move_ordering::{is_promotion, MoveOrderer, MAX_PLY};
+use crate::min_max::next_move_generator::{generate_capture_moves, generate_next_moves};
+use crate::min_max::search_options::SearchOptions;
+use crate::min_max::search_stats::SearchStats;
+use crate::min_max::score::Score;
+
+// a capture that can't bring the score within this margin of alpha is skipped
+const DELTA_MARGIN: i64 = 2 * PAWN_VALUE;
+
+// Resolves captures and promotions below the horizon so the static evaluation is
+// only taken in quiet positions. Scores are from the side to move's point of view.
+#[allow(clippy::too_many_arguments)]
+pub fn quiescence_search(
+    state: &Chessboard,
+    mut alpha: Score,
+    beta: Score,
+    player_color: PlayerColor,
+    ply: usize,
+    qs_ply: usize,
+    options: &SearchOptions,
+    move_orderer: &MoveOrderer,
+    stats: &mut SearchStats
+) -> Score {
+    stats.qnodes += 1;
+
+    if ply >= MAX_PLY || state.is_finished() {
+        return Score::centipawns(evaluate(state, player_color));
+    }
+
+    let in_check = state.is_in_check(player_color);
+
+    // stand pat: the side to move can always decline to capture, unless it is in check
+    let stand_pat = Score::centipawns(evaluate(state, player_color));
+    let mut value = if in_check { Score::mated_in(ply) } else { stand_pat };
+
+    if !in_check {
+        if value >= beta {
+            return value;
+        }
+        alpha = alpha.max(value);
+    }
+
+    let mut possible_moves = if in_check {
+        generate_next_moves(state, player_color)
+    } else {
+        generate_capture_moves(state, player_color, options.quiescence_checks && qs_ply == 0)
+    };
+
+    move_orderer.order_moves(state, &mut possible_moves, player_color, None, ply, None);
+
+    for next_move in possible_moves {
+        let victim = state.get_piece_type_color(player_color.opposite(), next_move.1);
+
+        // delta pruning: even winning the victim outright can't reach alpha
+        if !in_check
+            && victim != PieceType::None
+            && !is_promotion(state, next_move, player_color)
+            && stand_pat + piece_value(victim) + DELTA_MARGIN <= alpha
+        {
+            continue;
+        }
+
+        // captures that lose material on the exchange can't be what resolves the position
+        if !in_check
+            && victim != PieceType::None
+            && !is_promotion(state, next_move, player_color)
+            && !state.see_at_least(next_move, 0)
+        {
+            continue;
+        }
+
+        let mut next_state = state.clone();
+        let from = Chessboard::convert_index_to_square(next_move.0);
+        let to = Chessboard::convert_index_to_square(next_move.1);
+        next_state.perform_move(&from, &to, player_color).unwrap();
+
+        let result = -quiescence_search(
+            &next_state,
+            -beta,
+            -alpha,
+            player_color.opposite(),
+            ply + 1,
+            qs_ply + 1,
+            options,
+            move_orderer,
+            stats
+        );
+
+        value = value.max(result);
+        alpha = alpha.max(value);
+        if alpha >= beta {
+            break;
+        }
+    }
+
+    value
+}
@@ -0,0 +1,111 @@
+use std::fmt;
+use std::ops::{Add, Neg, Sub};
+
+// Scores at least this far from zero are mate scores: MATE_SCORE minus the number of
+// plies from the root to the mate, so a faster mate always scores higher.
+pub const MATE_SCORE: i64 = 1_000_000;
+pub const MATE_BOUND: i64 = MATE_SCORE - 1_000;
+
+// A search score from the side to move's point of view, in centipawns or as a mate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Score(i64);
+
+impl Score {
+    pub const DRAW: Score = Score(0);
+    // above any score the search can return
+    pub const INFINITY: Score = Score(MATE_SCORE + 1);
+
+    // evaluations are kept out of the mate range
+    pub fn centipawns(value: i64) -> Score {
+        Score(value.clamp(-MATE_BOUND + 1, MATE_BOUND - 1))
+    }
+
+    // the side to move mates in this many plies from the root
+    pub fn mate_in(ply: usize) -> Score {
+        Score(MATE_SCORE - ply as i64)
+    }
+
+    // the side to move is mated in this many plies from the root
+    pub fn mated_in(ply: usize) -> Score {
+        Score(-MATE_SCORE + ply as i64)
+    }
+
+    pub fn value(self) -> i64 {
+        self.0
+    }
+
+    pub fn is_mate(self) -> bool {
+        self.0.abs() >= MATE_BOUND && self.0.abs() <= MATE_SCORE
+    }
+
+    // full moves until mate, negative when the side to move is the one getting mated
+    pub fn mate_moves(self) -> Option<i64> {
+        if !self.is_mate() {
+            return None;
+        }
+
+        let plies = MATE_SCORE - self.0.abs();
+        if self.0 > 0 {
+            Some((plies + 1) / 2)
+        } else {
+            Some(-(plies / 2))
+        }
+    }
+
+    // Mates are stored relative to the node (mate in N from here) instead of the
+    // root, so they stay valid when the position is reached again at another ply.
+    pub fn to_node_relative(self, ply: usize) -> Score {
+        match self.0 {
+            value if value >= MATE_BOUND => Score(value + ply as i64),
+            value if value <= -MATE_BOUND => Score(value - ply as i64),
+            value => Score(value),
+        }
+    }
+
+    pub fn to_root_relative(self, ply: usize) -> Score {
+        match self.0 {
+            value if value >= MATE_BOUND => Score(value - ply as i64),
+            value if value <= -MATE_BOUND => Score(value + ply as i64),
+            value => Score(value),
+        }
+    }
+
+    // the raw value, for the packed table entry
+    pub(crate) fn from_raw(value: i64) -> Score {
+        Score(value)
+    }
+}
+
+impl Neg for Score {
+    type Output = Score;
+
+    fn neg(self) -> Score {
+        Score(-self.0)
+    }
+}
+
+impl Add<i64> for Score {
+    type Output = Score;
+
+    fn add(self, centipawns: i64) -> Score {
+        Score(self.0 + centipawns)
+    }
+}
+
+impl Sub<i64> for Score {
+    type Output = Score;
+
+    fn sub(self, centipawns: i64) -> Score {
+        Score(self.0 - centipawns)
+    }
+}
+
+// "mate 3", "mate -2" or "cp 35"
+impl fmt::Display for Score {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.mate_moves() {
+            Some(moves) => write!(f, "mate {}", moves),
+            None => write!(f, "cp {}", self.0),
+        }
+    }
+}
@@ -0,0 +1,54 @@
+use std::fmt;
+use std::time::Duration;
+use crate::chessboard::chessboard::Chessboard;
+use crate::min_max::score::Score;
+
+// Progress of a running search, reported after every completed iteration.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchInfo {
+    pub depth: usize,
+    // deepest ply reached, extensions included
+    pub seldepth: usize,
+    // which of the MultiPV lines this is, from 1
+    pub multipv: usize,
+    pub score: Score,
+    pub nodes: u64,
+    pub nps: u64,
+    pub time: Duration,
+    // permill of the transposition table in use
+    pub hashfull: usize,
+    pub principal_variation: Vec<(u64, u64)>,
+}
+
+impl SearchInfo {
+    pub fn nodes_per_second(nodes: u64, time: Duration) -> u64 {
+        match time.as_micros() {
+            0 => nodes * 1_000_000,
+            micros => (nodes as u128 * 1_000_000 / micros) as u64,
+        }
+    }
+}
+
+// the UCI info line: "depth 5 seldepth 7 multipv 1 score cp 20 nodes ... pv e2e4 e7e5"
+impl fmt::Display for SearchInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "depth {} seldepth {} multipv {} score {} nodes {} nps {} time {} hashfull {} pv",
+            self.depth,
+            self.seldepth,
+            self.multipv,
+            self.score,
+            self.nodes,
+            self.nps,
+            self.time.as_millis(),
+            self.hashfull
+        )?;
+
+        for next_move in self.principal_variation.iter() {
+            write!(f, " {}", Chessboard::convert_move_to_string(*next_move))?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,153 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use crate::chessboard::player::PlayerColor;
+use crate::min_max::time_manager::TimeControl;
+
+// When the search should stop on its own. Without any limit it searches to a small fixed depth.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SearchLimits {
+    pub depth: Option<usize>,
+    pub nodes: Option<u64>,
+    pub move_time: Option<Duration>,
+    pub white_time: Option<Duration>,
+    pub black_time: Option<Duration>,
+    pub white_increment: Duration,
+    pub black_increment: Duration,
+    pub moves_to_go: Option<u32>,
+    // search until stopped
+    pub infinite: bool,
+    // stop as soon as a mate in at most this many moves is found
+    pub mate: Option<u32>,
+    // Zobrist keys of the game's positions before the one searched, oldest first, so
+    // the search sees repetitions of them
+    pub game_history: Vec<u64>,
+}
+
+impl SearchLimits {
+    pub fn depth(depth: usize) -> SearchLimits {
+        SearchLimits {
+            depth: Some(depth),
+            ..SearchLimits::default()
+        }
+    }
+
+    pub fn nodes(nodes: u64) -> SearchLimits {
+        SearchLimits {
+            nodes: Some(nodes),
+            ..SearchLimits::default()
+        }
+    }
+
+    pub fn move_time(move_time: Duration) -> SearchLimits {
+        SearchLimits {
+            move_time: Some(move_time),
+            ..SearchLimits::default()
+        }
+    }
+
+    pub fn infinite() -> SearchLimits {
+        SearchLimits {
+            infinite: true,
+            ..SearchLimits::default()
+        }
+    }
+
+    pub fn mate(moves: u32) -> SearchLimits {
+        SearchLimits {
+            mate: Some(moves),
+            ..SearchLimits::default()
+        }
+    }
+
+    pub fn clock(
+        white_time: Duration,
+        black_time: Duration,
+        white_increment: Duration,
+        black_increment: Duration,
+        moves_to_go: Option<u32>
+    ) -> SearchLimits {
+        SearchLimits {
+            white_time: Some(white_time),
+            black_time: Some(black_time),
+            white_increment,
+            black_increment,
+            moves_to_go,
+            ..SearchLimits::default()
+        }
+    }
+
+    // the clock of the side to move, an infinite search ignores it
+    pub fn time_control(&self, player_color: PlayerColor) -> TimeControl {
+        if self.infinite {
+            return TimeControl::default();
+        }
+
+        let (remaining, increment) = match player_color {
+            PlayerColor::White => (self.white_time, self.white_increment),
+            PlayerColor::Black => (self.black_time, self.black_increment),
+        };
+
+        TimeControl {
+            remaining,
+            increment,
+            moves_to_go: self.moves_to_go,
+            move_time: self.move_time,
+        }
+    }
+
+    // anything that ends the search other than the default depth
+    pub fn is_limited(&self) -> bool {
+        self.depth.is_some()
+            || self.nodes.is_some()
+            || self.move_time.is_some()
+            || self.white_time.is_some()
+            || self.black_time.is_some()
+            || self.infinite
+            || self.mate.is_some()
+    }
+}
+
+// Shared between the caller and a running search: once raised, the search returns
+// the best move it has so far. A pondering search ignores the clock until the caller
+// reports a ponder hit, then goes on as a normal search with its clock starting there.
+#[derive(Debug, Clone, Default)]
+pub struct StopFlag {
+    stopped: Arc<AtomicBool>,
+    pondering: Arc<AtomicBool>,
+}
+
+impl StopFlag {
+    pub fn new() -> StopFlag {
+        StopFlag::default()
+    }
+
+    pub fn pondering() -> StopFlag {
+        StopFlag {
+            stopped: Arc::new(AtomicBool::new(false)),
+            pondering: Arc::new(AtomicBool::new(true)),
+        }
+    }
+
+    pub fn stop(&self) {
+        self.stopped.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_stopped(&self) -> bool {
+        self.stopped.load(Ordering::Relaxed)
+    }
+
+    // the opponent played the move that was pondered on
+    pub fn ponder_hit(&self) {
+        self.pondering.store(false, Ordering::Relaxed);
+    }
+
+    pub fn is_pondering(&self) -> bool {
+        self.pondering.load(Ordering::Relaxed)
+    }
+
+    pub fn reset(&self) {
+        self.stopped.store(false, Ordering::Relaxed);
+        self.pondering.store(false, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,175 @@
+use std::sync::Arc;
+use crate::exceptions::OptionError;
+use crate::min_max::skill::{level_from_elo, MAX_SKILL_LEVEL};
+use crate::notation::polyglot::{BookSelection, OpeningBook};
+#[cfg(feature = "syzygy")]
+use crate::min_max::syzygy::Tablebase;
+
+// Switches for the selective parts of the search and its extensions, so each can be
+// measured on its own.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchOptions {
+    pub null_move_pruning: bool,
+    pub late_move_reductions: bool,
+    pub reverse_futility_pruning: bool,
+    pub futility_pruning: bool,
+    pub razoring: bool,
+    pub late_move_pruning: bool,
+    pub check_extensions: bool,
+    pub singular_extensions: bool,
+    pub passed_pawn_extensions: bool,
+    // quiet checks at the first quiescence ply
+    pub quiescence_checks: bool,
+    // reduction = base + ln(depth) * ln(move number) / divisor
+    pub lmr_base: f64,
+    pub lmr_divisor: f64,
+    // Lazy SMP search threads, one is a plain deterministic search
+    pub threads: usize,
+    // root moves to find a score and principal variation for, best first
+    pub multi_pv: usize,
+    // one thread and no clock, so only the depth and node limits end the search and the
+    // same position and tables always give the same move and node count
+    pub deterministic: bool,
+    // centipawns a draw costs the side to move at the root, negative to seek draws
+    pub contempt: i64,
+    // endgame tables probed at the root and after captures and pawn moves
+    #[cfg(feature = "syzygy")]
+    pub tablebase: Option<Arc<Tablebase>>,
+    // Polyglot book get_best_move plays from before searching
+    pub book: Option<Arc<OpeningBook>>,
+    pub book_selection: BookSelection,
+    // 0 to MAX_SKILL_LEVEL, the top level playing at full strength
+    pub skill_level: usize,
+    // seeds the random choices of the book and of limited skill levels
+    pub seed: Option<u64>,
+}
+
+impl Default for SearchOptions {
+    fn default() -> SearchOptions {
+        SearchOptions {
+            null_move_pruning: true,
+            late_move_reductions: true,
+            reverse_futility_pruning: true,
+            futility_pruning: true,
+            razoring: true,
+            late_move_pruning: true,
+            check_extensions: true,
+            singular_extensions: true,
+            passed_pawn_extensions: true,
+            quiescence_checks: true,
+            lmr_base: 0.75,
+            lmr_divisor: 2.25,
+            threads: 1,
+            multi_pv: 1,
+            deterministic: false,
+            contempt: 0,
+            #[cfg(feature = "syzygy")]
+            tablebase: None,
+            book: None,
+            book_selection: BookSelection::Weighted,
+            skill_level: MAX_SKILL_LEVEL,
+            seed: None,
+        }
+    }
+}
+
+impl SearchOptions {
+    // a full-width search, the reference the selective one is measured against
+    pub fn full_width() -> SearchOptions {
+        SearchOptions {
+            null_move_pruning: false,
+            late_move_reductions: false,
+            reverse_futility_pruning: false,
+            futility_pruning: false,
+            razoring: false,
+            late_move_pruning: false,
+            ..SearchOptions::default()
+        }
+    }
+
+    pub fn deterministic() -> SearchOptions {
+        SearchOptions {
+            deterministic: true,
+            ..SearchOptions::default()
+        }
+    }
+
+    // the search threads to run, always one when deterministic
+    pub fn search_threads(&self) -> usize {
+        if self.deterministic { 1 } else { self.threads.max(1) }
+    }
+
+    // Sets an option by name, e.g. ("NullMove", "false") or ("LmrDivisor", "2.5").
+    pub fn set_option(&mut self, name: &str, value: &str) -> Result<(), OptionError> {
+        match name.to_ascii_lowercase().as_str() {
+            "nullmove" => self.null_move_pruning = parse_bool(value)?,
+            "lmr" => self.late_move_reductions = parse_bool(value)?,
+            "reversefutility" => self.reverse_futility_pruning = parse_bool(value)?,
+            "futility" => self.futility_pruning = parse_bool(value)?,
+            "razoring" => self.razoring = parse_bool(value)?,
+            "latemovepruning" => self.late_move_pruning = parse_bool(value)?,
+            "checkextensions" => self.check_extensions = parse_bool(value)?,
+            "singularextensions" => self.singular_extensions = parse_bool(value)?,
+            "passedpawnextensions" => self.passed_pawn_extensions = parse_bool(value)?,
+            "quiescencechecks" => self.quiescence_checks = parse_bool(value)?,
+            "lmrbase" => self.lmr_base = parse_positive(value)?,
+            "lmrdivisor" => self.lmr_divisor = parse_positive(value)?,
+            "threads" => self.threads = parse_count(value)?,
+            "multipv" => self.multi_pv = parse_count(value)?,
+            "deterministic" => self.deterministic = parse_bool(value)?,
+            "contempt" => self.contempt = value.parse().map_err(|_| OptionError::InvalidValue)?,
+            // an empty path turns the tables off
+            #[cfg(feature = "syzygy")]
+            "syzygypath" => self.tablebase = match value {
+                "" => None,
+                path => Some(Arc::new(Tablebase::open(path).map_err(|_| OptionError::InvalidValue)?)),
+            },
+            // an empty path turns the book off
+            "bookfile" => self.book = match value {
+                "" => None,
+                path => Some(Arc::new(OpeningBook::open(path).map_err(|_| OptionError::InvalidValue)?)),
+            },
+            "bookselection" => self.book_selection = match value.to_ascii_lowercase().as_str() {
+                "best" => BookSelection::Best,
+                "weighted" => BookSelection::Weighted,
+                _ => return Err(OptionError::InvalidValue),
+            },
+            "skilllevel" => self.skill_level = match value.parse() {
+                Ok(level) if level <= MAX_SKILL_LEVEL => level,
+                _ => return Err(OptionError::InvalidValue),
+            },
+            // sets the skill level playing at about the rating
+            "elo" => self.skill_level = level_from_elo(value.parse().map_err(|_| OptionError::InvalidValue)?),
+            // an empty seed goes back to unrepeatable choices
+            "seed" => self.seed = match value {
+                "" => None,
+                seed => Some(seed.parse().map_err(|_| OptionError::InvalidValue)?),
+            },
+            _ => return Err(OptionError::UnknownOption),
+        }
+
+        Ok(())
+    }
+}
+
+fn parse_bool(value: &str) -> Result<bool, OptionError> {
+    match value.to_ascii_lowercase().as_str() {
+        "true" | "on" => Ok(true),
+        "false" | "off" => Ok(false),
+        _ => Err(OptionError::InvalidValue),
+    }
+}
+
+fn parse_positive(value: &str) -> Result<f64, OptionError> {
+    match value.parse::<f64>() {
+        Ok(value) if value.is_finite() && value > 0.0 => Ok(value),
+        _ => Err(OptionError::InvalidValue),
+    }
+}
+
+fn parse_count(value: &str) -> Result<usize, OptionError> {
+    match value.parse::<usize>() {
+        Ok(value) if value > 0 => Ok(value),
+        _ => Err(OptionError::InvalidValue),
+    }
+}
@@ -0,0 +1,112 @@
+use std::fmt;
+use std::time::Duration;
+
+// One completed iteration of the main thread.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IterationStats {
+    pub depth: usize,
+    // nodes and quiescence nodes spent on this iteration alone
+    pub nodes: u64,
+    pub time: Duration,
+    // nodes of this iteration over the nodes of the one before
+    pub branching_factor: Option<f64>,
+}
+
+// Counters collected by a search, summed over all threads. The iterations are the
+// main thread's.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SearchStats {
+    pub nodes: u64,
+    pub qnodes: u64,
+    pub beta_cutoffs: u64,
+    // cutoffs by the first move searched, the measure of move ordering
+    pub first_move_cutoffs: u64,
+    pub tt_probes: u64,
+    pub tt_hits: u64,
+    pub null_move_cutoffs: u64,
+    // reduced moves that beat alpha and had to be searched again at full depth
+    pub lmr_researches: u64,
+    // positions scored by the endgame tables
+    pub tb_hits: u64,
+    pub iterations: Vec<IterationStats>,
+}
+
+impl SearchStats {
+    pub fn total_nodes(&self) -> u64 {
+        self.nodes + self.qnodes
+    }
+
+    // percent of the beta cutoffs
+    pub fn first_move_cutoff_rate(&self) -> f64 {
+        percentage(self.first_move_cutoffs, self.beta_cutoffs)
+    }
+
+    // percent of the probes
+    pub fn tt_hit_rate(&self) -> f64 {
+        percentage(self.tt_hits, self.tt_probes)
+    }
+
+    // closes an iteration that started when the search had visited iteration_start nodes
+    pub fn end_iteration(&mut self, depth: usize, iteration_start: u64, time: Duration) {
+        let nodes = self.total_nodes() - iteration_start;
+        let branching_factor = self.iterations
+            .last()
+            .filter(|previous| previous.nodes > 0)
+            .map(|previous| nodes as f64 / previous.nodes as f64);
+
+        self.iterations.push(IterationStats {
+            depth,
+            nodes,
+            time,
+            branching_factor,
+        });
+    }
+
+    // adds a helper thread's counters, its iterations stay its own
+    pub fn merge(&mut self, other: &SearchStats) {
+        self.nodes += other.nodes;
+        self.qnodes += other.qnodes;
+        self.beta_cutoffs += other.beta_cutoffs;
+        self.first_move_cutoffs += other.first_move_cutoffs;
+        self.tt_probes += other.tt_probes;
+        self.tt_hits += other.tt_hits;
+        self.null_move_cutoffs += other.null_move_cutoffs;
+        self.lmr_researches += other.lmr_researches;
+        self.tb_hits += other.tb_hits;
+    }
+}
+
+fn percentage(part: u64, whole: u64) -> f64 {
+    match whole {
+        0 => 0.0,
+        whole => part as f64 * 100.0 / whole as f64,
+    }
+}
+
+impl fmt::Display for SearchStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "nodes {} qnodes {} cutoffs {} first move {:.1}% tt hits {:.1}% of {} null move cutoffs {} lmr re-searches {} tb hits {}",
+            self.nodes,
+            self.qnodes,
+            self.beta_cutoffs,
+            self.first_move_cutoff_rate(),
+            self.tt_hit_rate(),
+            self.tt_probes,
+            self.null_move_cutoffs,
+            self.lmr_researches,
+            self.tb_hits
+        )?;
+
+        for iteration in self.iterations.iter() {
+            write!(f, "depth {:>2}: {:>10} nodes {:>7} ms", iteration.depth, iteration.nodes, iteration.time.as_millis())?;
+            if let Some(branching_factor) = iteration.branching_factor {
+                write!(f, ", branching factor {:.2}", branching_factor)?;
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,75 @@
+use crate::evaluator::PAWN_VALUE;
+use crate::min_max::min_max::SearchLine;
+use crate::min_max::search_limits::SearchLimits;
+
+// Strength limiting for weaker opponents. Below the top level the search is cut short
+// and the move is picked among a few of the best root moves, a weaker level straying
+// further from the best one.
+pub const MAX_SKILL_LEVEL: usize = 20;
+// root moves a limited level chooses from
+pub const SKILL_MULTI_PV: usize = 4;
+// the rough ratings of level 0 and of the level below the top
+pub const SKILL_MIN_ELO: u32 = 800;
+pub const SKILL_MAX_ELO: u32 = 2200;
+
+pub fn is_limited(level: usize) -> bool {
+    level < MAX_SKILL_LEVEL
+}
+
+// the level playing at about the rating, the top level above the range
+pub fn level_from_elo(elo: u32) -> usize {
+    if elo > SKILL_MAX_ELO {
+        return MAX_SKILL_LEVEL;
+    }
+
+    let elo = elo.max(SKILL_MIN_ELO) - SKILL_MIN_ELO;
+    (elo as usize * (MAX_SKILL_LEVEL - 1)) / (SKILL_MAX_ELO - SKILL_MIN_ELO) as usize
+}
+
+// Level 0 searches one ply and a thousand nodes, every two levels double the nodes
+// and add a ply. Tighter depth and node limits of the caller still apply, and so does
+// its clock or move time, whichever runs out first. An infinite search stops at the
+// level's limits too.
+pub fn limit_search(limits: &SearchLimits, level: usize) -> SearchLimits {
+    let depth = 1 + level / 2;
+    let nodes = 1_000 << (level / 2);
+
+    SearchLimits {
+        depth: Some(limits.depth.map_or(depth, |limit| limit.min(depth))),
+        nodes: Some(limits.nodes.map_or(nodes, |limit| limit.min(nodes))),
+        infinite: false,
+        ..limits.clone()
+    }
+}
+
+// Picks one of the lines, best first. Each gets a random bonus as large as the spread
+// of the scores, between a quarter pawn and a pawn, and a share of how far it is behind
+// the best line that grows as the level drops. `random` seeds the draws.
+pub fn pick_line(lines: &[SearchLine], level: usize, random: u64) -> &SearchLine {
+    let weakness = 120 - 2 * level.min(MAX_SKILL_LEVEL) as i64;
+    let top = lines[0].score.value();
+    let delta = (top - lines[lines.len() - 1].score.value()).clamp(PAWN_VALUE / 4, PAWN_VALUE);
+
+    let mut state = random;
+    let mut best = &lines[0];
+    let mut best_value = i64::MIN;
+    for line in lines {
+        let score = line.score.value();
+        let push = (weakness * (top - score) + delta * (next_random(&mut state) % weakness as u64) as i64) / 128;
+        if score + push > best_value {
+            best = line;
+            best_value = score + push;
+        }
+    }
+
+    best
+}
+
+// splitmix64
+fn next_random(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
@@ -0,0 +1,117 @@
+use std::fmt;
+use shakmaty::fen::Fen;
+use shakmaty::uci::UciMove;
+use shakmaty::{CastlingMode, Chess, Position, PositionError, Role};
+use shakmaty_syzygy::{AmbiguousWdl, Wdl};
+use crate::chessboard::chessboard::Chessboard;
+use crate::exceptions::TablebaseError;
+use crate::min_max::score::{Score, MATE_BOUND};
+use crate::min_max::move_ordering::MAX_PLY;
+
+// a won tablebase position scores below every mate the search can find itself, minus
+// the plies to it so the search still heads for the nearest one
+const TABLEBASE_WIN: i64 = MATE_BOUND - MAX_PLY as i64 - 1;
+
+// Syzygy WDL and DTZ tables from a local directory, probed on a copy of the board
+// converted through its FEN.
+pub struct Tablebase {
+    path: String,
+    tables: shakmaty_syzygy::Tablebase<Chess>,
+}
+
+impl Tablebase {
+    pub fn open(path: &str) -> Result<Tablebase, TablebaseError> {
+        let mut tables = shakmaty_syzygy::Tablebase::new();
+        match tables.add_directory(path) {
+            Err(_) => Err(TablebaseError::DirectoryNotFound),
+            Ok(0) => Err(TablebaseError::NoTables),
+            Ok(_) => Ok(Tablebase {
+                path: path.to_string(),
+                tables,
+            }),
+        }
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    // the most pieces, kings included, of any table found
+    pub fn max_pieces(&self) -> usize {
+        self.tables.max_pieces()
+    }
+
+    // The score of the position for the side to move, when it comes right after a capture or
+    // pawn move as the WDL tables assume. Wins and losses the fifty-move rule saves are draws.
+    pub fn probe_wdl(&self, state: &Chessboard, ply: usize) -> Option<Score> {
+        if state.halfmove_clock != 0 {
+            return None;
+        }
+
+        let position = self.position(state)?;
+        let wdl = self.tables.probe_wdl_after_zeroing(&position).ok()?;
+
+        Some(wdl_score(wdl, ply))
+    }
+
+    // The move that keeps the best result under the fifty-move rule and its score.
+    // Underpromotions are left to the search, which only promotes to a queen.
+    pub fn probe_root(&self, state: &Chessboard) -> Option<((u64, u64), Score)> {
+        let position = self.position(state)?;
+        let (best_move, _) = self.tables.best_move(&position).ok()??;
+
+        if best_move.promotion().is_some_and(|role| role != Role::Queen) {
+            return None;
+        }
+
+        // the DTZ that comes with the move is the opponent's, after it
+        let dtz = self.tables.probe_dtz(&position).ok()?;
+        let wdl = AmbiguousWdl::from_dtz_and_halfmoves(dtz, state.halfmove_clock as u32).after_zeroing();
+        match best_move.to_uci(CastlingMode::Standard) {
+            UciMove::Normal { from, to, .. } => Some(((u32::from(from) as u64, u32::from(to) as u64), wdl_score(wdl, 0))),
+            _ => None,
+        }
+    }
+
+    // only positions within the tables and without castling rights, which the tables leave out
+    fn position(&self, state: &Chessboard) -> Option<Chess> {
+        if state.get_board().count_ones() as usize > self.max_pieces() {
+            return None;
+        }
+
+        let fen = Fen::from_ascii(state.to_fen().as_bytes()).ok()?;
+        let position: Chess = fen
+            .into_position(CastlingMode::Standard)
+            .or_else(PositionError::ignore_invalid_ep_square)
+            .ok()?;
+
+        match position.castles().is_empty() {
+            true => Some(position),
+            false => None,
+        }
+    }
+}
+
+fn wdl_score(wdl: Wdl, ply: usize) -> Score {
+    match wdl {
+        Wdl::Win => Score::centipawns(TABLEBASE_WIN - ply as i64),
+        Wdl::Loss => Score::centipawns(-TABLEBASE_WIN + ply as i64),
+        Wdl::CursedWin | Wdl::Draw | Wdl::BlessedLoss => Score::DRAW,
+    }
+}
+
+// the same directory holds the same tables
+impl PartialEq for Tablebase {
+    fn eq(&self, other: &Tablebase) -> bool {
+        self.path == other.path
+    }
+}
+
+impl fmt::Debug for Tablebase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Tablebase")
+            .field("path", &self.path)
+            .field("max_pieces", &self.max_pieces())
+            .finish()
+    }
+}
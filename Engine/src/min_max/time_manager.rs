@@ -0,0 +1,117 @@
+use std::time::{Duration, Instant};
+
+// moves we assume are left in the game when the GUI doesn't say
+const DEFAULT_MOVES_TO_GO: u32 = 30;
+// never plan for more than this many moves, the clock would be spread too thin
+const MAX_MOVES_TO_GO: u32 = 50;
+// kept in reserve for communication and move application
+const MOVE_OVERHEAD: Duration = Duration::from_millis(30);
+// how much of the remaining time a single move may use at most
+const MAX_TIME_FRACTION: u32 = 3;
+// hard limit = soft limit * HARD_LIMIT_FACTOR, when the clock allows it
+const HARD_LIMIT_FACTOR: u32 = 4;
+
+#[derive(Debug, Clone, Default)]
+pub struct TimeControl {
+    // time left on the engine's clock
+    pub remaining: Option<Duration>,
+    pub increment: Duration,
+    pub moves_to_go: Option<u32>,
+    // exact time to spend on this move, overrides the clock
+    pub move_time: Option<Duration>,
+}
+
+impl TimeControl {
+    pub fn with_move_time(move_time: Duration) -> TimeControl {
+        TimeControl {
+            move_time: Some(move_time),
+            ..TimeControl::default()
+        }
+    }
+
+    pub fn with_clock(remaining: Duration, increment: Duration, moves_to_go: Option<u32>) -> TimeControl {
+        TimeControl {
+            remaining: Some(remaining),
+            increment,
+            moves_to_go,
+            move_time: None,
+        }
+    }
+}
+
+// The soft limit decides whether another iteration is started,
+// the hard limit aborts an iteration that is already running.
+#[derive(Debug, Clone)]
+pub struct TimeManager {
+    start: Instant,
+    soft_limit: Option<Duration>,
+    hard_limit: Option<Duration>,
+}
+
+impl TimeManager {
+    pub fn new(time_control: &TimeControl) -> TimeManager {
+        let (soft_limit, hard_limit) = TimeManager::allocate(time_control);
+
+        TimeManager {
+            start: Instant::now(),
+            soft_limit,
+            hard_limit,
+        }
+    }
+
+    fn allocate(time_control: &TimeControl) -> (Option<Duration>, Option<Duration>) {
+        if let Some(move_time) = time_control.move_time {
+            let limit = move_time.saturating_sub(MOVE_OVERHEAD);
+            return (Some(limit), Some(limit));
+        }
+
+        let remaining = match time_control.remaining {
+            Some(remaining) => remaining.saturating_sub(MOVE_OVERHEAD),
+            None => return (None, None),
+        };
+
+        let moves_to_go = time_control
+            .moves_to_go
+            .unwrap_or(DEFAULT_MOVES_TO_GO)
+            .clamp(1, MAX_MOVES_TO_GO);
+
+        let maximum = remaining / MAX_TIME_FRACTION;
+        let soft_limit = (remaining / moves_to_go + time_control.increment * 3 / 4).min(maximum);
+        let hard_limit = (soft_limit * HARD_LIMIT_FACTOR).min(maximum).max(soft_limit);
+
+        (Some(soft_limit), Some(hard_limit))
+    }
+
+    // the clock starts over, e.g. when pondering turns into the real search
+    pub fn restart(&mut self) {
+        self.start = Instant::now();
+    }
+
+    pub fn is_limited(&self) -> bool {
+        self.hard_limit.is_some()
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.start.elapsed()
+    }
+
+    pub fn soft_limit(&self) -> Option<Duration> {
+        self.soft_limit
+    }
+
+    pub fn hard_limit(&self) -> Option<Duration> {
+        self.hard_limit
+    }
+
+    pub fn deadline(&self) -> Option<Instant> {
+        self.hard_limit.map(|limit| self.start + limit)
+    }
+
+    pub fn should_start_iteration(&self) -> bool {
+        self.soft_limit.is_none_or(|limit| self.elapsed() < limit)
+    }
+
+    pub fn is_time_up(&self) -> bool {
+        self.hard_limit.is_some_and(|limit| self.elapsed() >= limit)
+    }
+}
@@ -0,0 +1,181 @@
+use std::sync::atomic::{AtomicU64, AtomicU8, Ordering};
+use crate::min_max::score::Score;
+
+pub const DEFAULT_SIZE_MB: usize = 16;
+
+// two u64 words per slot
+const ENTRY_SIZE: usize = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bound {
+    Exact,
+    // the score is a lower bound (the search failed high)
+    Lower,
+    // the score is an upper bound (the search failed low)
+    Upper,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TableEntry {
+    pub depth: usize,
+    pub bound: Bound,
+    pub score: Score,
+    pub best_move: Option<(u64, u64)>,
+    pub age: u8,
+}
+
+// Each slot stores (key ^ data, data) in two atomics. A slot torn by a concurrent
+// write no longer XORs back to the probed key and is treated as a miss, so the
+// table can be shared between search tasks without locking.
+pub struct TranspositionTable {
+    keys: Vec<AtomicU64>,
+    data: Vec<AtomicU64>,
+    mask: usize,
+    age: AtomicU8,
+}
+
+impl TranspositionTable {
+    pub fn new(size_mb: usize) -> TranspositionTable {
+        let requested = (size_mb.max(1) * 1024 * 1024 / ENTRY_SIZE).max(1);
+        // largest power of two that fits, so the index is a mask of the key
+        let num_entries = 1 << (usize::BITS - 1 - requested.leading_zeros());
+
+        TranspositionTable {
+            keys: (0..num_entries).map(|_| AtomicU64::new(0)).collect(),
+            data: (0..num_entries).map(|_| AtomicU64::new(0)).collect(),
+            mask: num_entries - 1,
+            age: AtomicU8::new(0),
+        }
+    }
+
+    pub fn num_entries(&self) -> usize {
+        self.mask + 1
+    }
+
+    pub fn clear(&self) {
+        for i in 0..self.num_entries() {
+            self.keys[i].store(0, Ordering::Relaxed);
+            self.data[i].store(0, Ordering::Relaxed);
+        }
+        self.age.store(0, Ordering::Relaxed);
+    }
+
+    // called once per search so entries from older searches get replaced first
+    pub fn new_search(&self) {
+        self.age.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn probe(&self, key: u64, ply: usize) -> Option<TableEntry> {
+        let index = key as usize & self.mask;
+        let data = self.data[index].load(Ordering::Relaxed);
+        let stored_key = self.keys[index].load(Ordering::Relaxed);
+
+        if data == 0 || stored_key ^ data != key {
+            return None;
+        }
+
+        let mut entry = unpack(data);
+        entry.score = entry.score.to_root_relative(ply);
+        Some(entry)
+    }
+
+    pub fn store(
+        &self,
+        key: u64,
+        depth: usize,
+        bound: Bound,
+        score: Score,
+        best_move: Option<(u64, u64)>,
+        ply: usize,
+    ) {
+        let index = key as usize & self.mask;
+        let age = self.age.load(Ordering::Relaxed);
+        let old_data = self.data[index].load(Ordering::Relaxed);
+        let old_key = self.keys[index].load(Ordering::Relaxed) ^ old_data;
+
+        let mut best_move = best_move;
+        if old_data != 0 {
+            let old = unpack(old_data);
+            let replace = old_key != key && old.age != age
+                || depth >= old.depth
+                || bound == Bound::Exact;
+            if !replace {
+                return;
+            }
+
+            // keep the old move rather than losing move ordering information
+            if best_move.is_none() && old_key == key {
+                best_move = old.best_move;
+            }
+        }
+
+        let entry = TableEntry {
+            depth,
+            bound,
+            score: score.to_node_relative(ply),
+            best_move,
+            age,
+        };
+        let data = pack(&entry);
+        self.keys[index].store(key ^ data, Ordering::Relaxed);
+        self.data[index].store(data, Ordering::Relaxed);
+    }
+
+    // permill of the first 1000 slots used by the current search
+    pub fn hashfull(&self) -> usize {
+        let age = self.age.load(Ordering::Relaxed);
+        let sample = self.num_entries().min(1000);
+        let used = (0..sample)
+            .filter(|i| {
+                let data = self.data[*i].load(Ordering::Relaxed);
+                data != 0 && unpack(data).age == age
+            })
+            .count();
+
+        used * 1000 / sample
+    }
+}
+
+// data layout: score (32 bits) | from (6) | to (6) | has move (1) | depth (8) | bound (2) | age (8)
+// bound is stored as 1..=3 so a used slot is never all zeroes
+fn pack(entry: &TableEntry) -> u64 {
+    let score = entry.score.value().clamp(i32::MIN as i64, i32::MAX as i64) as i32 as u32 as u64;
+    let (from, to, has_move) = match entry.best_move {
+        Some((from, to)) => (from, to, 1),
+        None => (0, 0, 0),
+    };
+    let bound = match entry.bound {
+        Bound::Exact => 1,
+        Bound::Lower => 2,
+        Bound::Upper => 3,
+    };
+
+    score
+        | from << 32
+        | to << 38
+        | has_move << 44
+        | (entry.depth.min(255) as u64) << 45
+        | bound << 53
+        | (entry.age as u64) << 55
+}
+
+fn unpack(data: u64) -> TableEntry {
+    let best_move = if (data >> 44) & 1 != 0 {
+        Some(((data >> 32) & 0x3F, (data >> 38) & 0x3F))
+    } else {
+        None
+    };
+    let bound = match (data >> 53) & 0b11 {
+        1 => Bound::Exact,
+        2 => Bound::Lower,
+        _ => Bound::Upper,
+    };
+
+    TableEntry {
+        depth: ((data >> 45) & 0xFF) as usize,
+        bound,
+        score: Score::from_raw(data as u32 as i32 as i64),
+        best_move,
+        age: (data >> 55) as u8,
+    }
+}
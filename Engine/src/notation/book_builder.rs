@@ -0,0 +1,136 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use crate::chessboard::chessboard::Chessboard;
+use crate::chessboard::game::GameResult;
+use crate::chessboard::player::PlayerColor;
+use crate::exceptions::PgnError;
+use crate::notation::pgn::{parse_pgn, PgnGame};
+use crate::notation::polyglot::{encode_move, BookEntry, OpeningBook};
+
+// Which moves of the games make it into the book.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BookFilters {
+    // only moves played by a side rated at least this, 0 takes unrated games too
+    pub min_rating: u32,
+    // only the first moves of each game
+    pub max_ply: usize,
+    // only moves played this often from their position
+    pub min_occurrences: u32,
+}
+
+impl Default for BookFilters {
+    fn default() -> BookFilters {
+        BookFilters {
+            min_rating: 0,
+            max_ply: 40,
+            min_occurrences: 3,
+        }
+    }
+}
+
+// how often a move was played from a position and the points it scored for its side
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct MoveStats {
+    occurrences: u32,
+    // 2 for a win, 1 for a draw or an unknown result, 0 for a loss
+    points: u64,
+}
+
+// Collects the moves of PGN games into a Polyglot book. A move's weight is the points
+// it scored, so it grows with how often it was played and how well it did.
+#[derive(Debug, Clone)]
+pub struct BookBuilder {
+    filters: BookFilters,
+    moves: HashMap<(u64, u16), MoveStats>,
+    // games whose moves were added, and those left out for illegal moves or bad tags
+    pub games_added: usize,
+    pub games_skipped: usize,
+}
+
+impl BookBuilder {
+    pub fn new(filters: BookFilters) -> BookBuilder {
+        BookBuilder {
+            filters,
+            moves: HashMap::new(),
+            games_added: 0,
+            games_skipped: 0,
+        }
+    }
+
+    // Adds every game of a PGN file, skipping the ones that can't be replayed.
+    // Only a file that can't be read as PGN at all is an error.
+    pub fn add_pgn(&mut self, contents: &str) -> Result<(), PgnError> {
+        for game in parse_pgn(contents)? {
+            match self.add_game(&game) {
+                Ok(()) => self.games_added += 1,
+                Err(_) => self.games_skipped += 1,
+            }
+        }
+
+        Ok(())
+    }
+
+    // Replays the game up to the ply limit, counting the moves of the sides rated high enough.
+    pub fn add_game(&mut self, game: &PgnGame) -> Result<(), PgnError> {
+        let record = game.to_record()?;
+        let white_counts = game.white_elo().unwrap_or(0) >= self.filters.min_rating;
+        let black_counts = game.black_elo().unwrap_or(0) >= self.filters.min_rating;
+
+        let mut state = record.start.clone();
+        for next_move in record.moves.iter().take(self.filters.max_ply) {
+            let color = state.side_to_move;
+            let counts = match color {
+                PlayerColor::White => white_counts,
+                PlayerColor::Black => black_counts,
+            };
+
+            if counts {
+                let stats = self.moves
+                    .entry((state.polyglot_hash(), encode_move(&state, *next_move)))
+                    .or_default();
+                stats.occurrences += 1;
+                stats.points += result_points(record.result, color);
+            }
+
+            let from = Chessboard::convert_index_to_square(next_move.0);
+            let to = Chessboard::convert_index_to_square(next_move.1);
+            // replayed once already by to_record
+            state.perform_move(&from, &to, color).unwrap();
+        }
+
+        Ok(())
+    }
+
+    // The book of the moves played often enough. Weights are scaled down together when
+    // the heaviest would not fit in the 16 bits Polyglot has for them.
+    pub fn build(&self) -> OpeningBook {
+        // the heaviest move of a position first, as Polyglot books list them
+        let mut moves: Vec<_> = self.moves
+            .iter()
+            .filter(|(_, stats)| stats.occurrences >= self.filters.min_occurrences)
+            .collect();
+        moves.sort_by_key(|((key, raw_move), stats)| (*key, Reverse(stats.points), *raw_move));
+        let heaviest = moves.iter().map(|(_, stats)| stats.points).max().unwrap_or(0);
+        let scale = heaviest.div_ceil(u16::MAX as u64).max(1);
+
+        let entries = moves
+            .into_iter()
+            .map(|((key, raw_move), stats)| BookEntry {
+                key: *key,
+                raw_move: *raw_move,
+                weight: (stats.points / scale) as u16,
+                learn: 0,
+            })
+            .collect();
+
+        OpeningBook::new(entries)
+    }
+}
+
+fn result_points(result: Option<GameResult>, color: PlayerColor) -> u64 {
+    match (result, color) {
+        (Some(GameResult::WhiteWins), PlayerColor::White) | (Some(GameResult::BlackWins), PlayerColor::Black) => 2,
+        (Some(GameResult::WhiteWins), PlayerColor::Black) | (Some(GameResult::BlackWins), PlayerColor::White) => 0,
+        (Some(GameResult::Draw), _) | (None, _) => 1,
+    }
+}
@@ -0,0 +1,272 @@
+use crate::chessboard::chessboard::Chessboard;
+use crate::exceptions::{EpdError, FenError};
+use crate::notation::san::{line_to_san, move_to_san, parse_san, parse_san_line};
+use std::fmt;
+
+// Extended Position Description: the first four FEN fields followed by
+// `opcode operand ...;` operations, e.g.
+// r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - bm Bb5; id "Ruy Lopez";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EpdOperation {
+    pub opcode: String,
+    pub operands: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Epd {
+    pub position: Chessboard,
+    pub operations: Vec<EpdOperation>,
+}
+
+impl Epd {
+    pub fn new(position: Chessboard) -> Epd {
+        Epd {
+            position,
+            operations: vec![],
+        }
+    }
+
+    pub fn parse(line: &str) -> Result<Epd, EpdError> {
+        let mut rest = line.trim();
+        let mut fields = vec![];
+        for _ in 0..4 {
+            if rest.is_empty() {
+                return Err(EpdError::InvalidPosition(FenError::WrongFieldCount));
+            }
+
+            let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            fields.push(&rest[..end]);
+            rest = rest[end..].trim_start();
+        }
+
+        let mut position =
+            Chessboard::from_fen(&fields.join(" ")).map_err(EpdError::InvalidPosition)?;
+        let operations = parse_operations(rest)?;
+
+        // the clocks are not part of the four fields, EPD carries them as operations
+        for operation in &operations {
+            let value = operation
+                .operands
+                .first()
+                .and_then(|operand| operand.parse().ok());
+            match (operation.opcode.as_str(), value) {
+                ("hmvc", Some(value)) => position.halfmove_clock = value,
+                ("fmvn", Some(value)) => position.fullmove_number = value,
+                _ => {}
+            }
+        }
+
+        Ok(Epd {
+            position,
+            operations,
+        })
+    }
+
+    pub fn get_operation(&self, opcode: &str) -> Option<&[String]> {
+        self.operations
+            .iter()
+            .find(|operation| operation.opcode == opcode)
+            .map(|operation| operation.operands.as_slice())
+    }
+
+    pub fn set_operation(&mut self, opcode: &str, operands: Vec<String>) {
+        match self
+            .operations
+            .iter_mut()
+            .find(|operation| operation.opcode == opcode)
+        {
+            Some(operation) => operation.operands = operands,
+            None => self.operations.push(EpdOperation {
+                opcode: opcode.to_string(),
+                operands,
+            }),
+        }
+    }
+
+    pub fn remove_operation(&mut self, opcode: &str) {
+        self.operations
+            .retain(|operation| operation.opcode != opcode);
+    }
+
+    pub fn id(&self) -> Option<&str> {
+        self.get_string("id")
+    }
+
+    // c0 ... c9
+    pub fn comment(&self, index: usize) -> Option<&str> {
+        self.get_string(&format!("c{}", index))
+    }
+
+    pub fn best_moves(&self) -> Result<Vec<(u64, u64)>, EpdError> {
+        self.get_moves("bm")
+    }
+
+    pub fn avoid_moves(&self) -> Result<Vec<(u64, u64)>, EpdError> {
+        self.get_moves("am")
+    }
+
+    pub fn principal_variation(&self) -> Result<Vec<(u64, u64)>, EpdError> {
+        let operands: Vec<&str> = match self.get_operation("pv") {
+            Some(operands) => operands.iter().map(|operand| operand.as_str()).collect(),
+            None => return Ok(vec![]),
+        };
+
+        parse_san_line(&self.position, &operands).map_err(EpdError::InvalidMove)
+    }
+
+    // centipawn evaluation
+    pub fn centipawn_evaluation(&self) -> Option<i64> {
+        self.get_number("ce")
+    }
+
+    // direct mate fullmove count
+    pub fn direct_mate(&self) -> Option<i64> {
+        self.get_number("dm")
+    }
+
+    // analysis count depth
+    pub fn analysis_depth(&self) -> Option<i64> {
+        self.get_number("acd")
+    }
+
+    // alternative moves from the position, e.g. "bm" or "am"
+    pub fn set_moves(&mut self, opcode: &str, moves: &[(u64, u64)]) -> Result<(), EpdError> {
+        let operands = moves
+            .iter()
+            .map(|next_move| move_to_san(&self.position, *next_move))
+            .collect::<Result<Vec<String>, _>>()
+            .map_err(EpdError::InvalidMove)?;
+
+        self.set_operation(opcode, operands);
+        Ok(())
+    }
+
+    pub fn set_principal_variation(&mut self, line: &[(u64, u64)]) -> Result<(), EpdError> {
+        let operands = line_to_san(&self.position, line).map_err(EpdError::InvalidMove)?;
+        self.set_operation("pv", operands);
+        Ok(())
+    }
+
+    fn get_moves(&self, opcode: &str) -> Result<Vec<(u64, u64)>, EpdError> {
+        match self.get_operation(opcode) {
+            Some(operands) => operands
+                .iter()
+                .map(|san| parse_san(&self.position, san))
+                .collect::<Result<Vec<(u64, u64)>, _>>()
+                .map_err(EpdError::InvalidMove),
+            None => Ok(vec![]),
+        }
+    }
+
+    fn get_string(&self, opcode: &str) -> Option<&str> {
+        self.get_operation(opcode)?
+            .first()
+            .map(|operand| operand.as_str())
+    }
+
+    fn get_number(&self, opcode: &str) -> Option<i64> {
+        self.get_operation(opcode)?.first()?.parse().ok()
+    }
+}
+
+impl fmt::Display for Epd {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.position.fen_position())?;
+
+        for operation in &self.operations {
+            write!(f, " {}", operation.opcode)?;
+            for operand in &operation.operands {
+                if is_string_opcode(&operation.opcode) || needs_quotes(operand) {
+                    write!(f, " \"{}\"", operand.replace('\\', "\\\\").replace('"', "\\\""))?;
+                } else {
+                    write!(f, " {}", operand)?;
+                }
+            }
+            write!(f, ";")?;
+        }
+
+        Ok(())
+    }
+}
+
+// one record per non-empty line, lines starting with '#' are skipped
+pub fn parse_epd_file(contents: &str) -> Result<Vec<Epd>, EpdError> {
+    contents
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(Epd::parse)
+        .collect()
+}
+
+fn parse_operations(text: &str) -> Result<Vec<EpdOperation>, EpdError> {
+    let mut operations = vec![];
+    let mut chars = text.chars().peekable();
+
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        if chars.peek().is_none() {
+            break;
+        }
+
+        let mut opcode = String::new();
+        while let Some(c) = chars.next_if(|c| !c.is_whitespace() && *c != ';') {
+            opcode.push(c);
+        }
+
+        let is_valid_opcode = opcode.starts_with(|c: char| c.is_ascii_alphabetic())
+            && opcode
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if !is_valid_opcode {
+            return Err(EpdError::InvalidOperation);
+        }
+
+        let mut operands = vec![];
+        loop {
+            while chars.next_if(|c| c.is_whitespace()).is_some() {}
+
+            match chars.next() {
+                // the final ';' is often missing in the wild
+                None | Some(';') => break,
+                Some('"') => {
+                    let mut operand = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('"') => break,
+                            // \" and \\ as written by Display
+                            Some('\\') => match chars.next() {
+                                Some(c) => operand.push(c),
+                                None => return Err(EpdError::UnterminatedString),
+                            },
+                            Some(c) => operand.push(c),
+                            None => return Err(EpdError::UnterminatedString),
+                        }
+                    }
+                    operands.push(operand);
+                }
+                Some(c) => {
+                    let mut operand = c.to_string();
+                    while let Some(c) = chars.next_if(|c| !c.is_whitespace() && *c != ';') {
+                        operand.push(c);
+                    }
+                    operands.push(operand);
+                }
+            }
+        }
+
+        operations.push(EpdOperation { opcode, operands });
+    }
+
+    Ok(operations)
+}
+
+fn is_string_opcode(opcode: &str) -> bool {
+    opcode == "id"
+        || (opcode.len() == 2 && opcode.starts_with('c') && opcode.as_bytes()[1].is_ascii_digit())
+}
+
+fn needs_quotes(operand: &str) -> bool {
+    operand.is_empty() || operand.contains(|c: char| c.is_whitespace() || c == ';' || c == '"')
+}
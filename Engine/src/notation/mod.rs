@@ -0,0 +1,5 @@
+pub(crate) mod san;
+pub(crate) mod epd;
+pub(crate) mod polyglot;
+pub(crate) mod pgn;
+pub(crate) mod book_builder;
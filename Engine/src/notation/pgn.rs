@@ -0,0 +1,206 @@
+use crate::chessboard::chessboard::Chessboard;
+use crate::chessboard::game::{GameRecord, GameResult};
+use crate::exceptions::{PgnError, SanError};
+use crate::notation::san::parse_san;
+
+// Portable Game Notation: tag pairs like [White "Carlsen"] followed by the movetext,
+// SAN moves with move numbers, comments, NAGs and variations, ended by the result.
+// Comments, NAGs and variations are skipped, only the main line is kept.
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PgnGame {
+    pub tags: Vec<(String, String)>,
+    // the SAN moves of the main line
+    pub moves: Vec<String>,
+    pub result: Option<GameResult>,
+}
+
+impl PgnGame {
+    fn new() -> PgnGame {
+        PgnGame {
+            tags: vec![],
+            moves: vec![],
+            result: None,
+        }
+    }
+
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(tag, _)| tag == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    // "?" or "-" for unknown ratings, as PGN has it
+    pub fn white_elo(&self) -> Option<u32> {
+        self.tag("WhiteElo")?.parse().ok()
+    }
+
+    pub fn black_elo(&self) -> Option<u32> {
+        self.tag("BlackElo")?.parse().ok()
+    }
+
+    // the FEN tag's position, the standard one without it
+    pub fn start_position(&self) -> Result<Chessboard, PgnError> {
+        match self.tag("FEN") {
+            Some(fen) => Chessboard::from_fen(fen).map_err(PgnError::InvalidPosition),
+            None => Ok(Chessboard::new()),
+        }
+    }
+
+    // Replays the moves through the rules, any illegal or unsupported move fails the game.
+    pub fn to_record(&self) -> Result<GameRecord, PgnError> {
+        let mut record = GameRecord::new(self.start_position()?);
+        let mut state = record.start.clone();
+
+        for san in &self.moves {
+            let next_move = parse_san(&state, san).map_err(PgnError::InvalidMove)?;
+            let color = state.side_to_move;
+            let from = Chessboard::convert_index_to_square(next_move.0);
+            let to = Chessboard::convert_index_to_square(next_move.1);
+            state
+                .perform_move(&from, &to, color)
+                .map_err(|_| PgnError::InvalidMove(SanError::IllegalMove))?;
+            record.push_move(next_move);
+        }
+
+        record.result = self.result;
+        Ok(record)
+    }
+}
+
+// every game of a PGN file or string, in order
+pub fn parse_pgn(contents: &str) -> Result<Vec<PgnGame>, PgnError> {
+    let mut games = vec![];
+    let mut game = PgnGame::new();
+    let mut chars = contents.char_indices().peekable();
+
+    while let Some((start, c)) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {}
+            '[' => {
+                // tags after movetext without a result start the next game
+                if !game.moves.is_empty() {
+                    games.push(game);
+                    game = PgnGame::new();
+                }
+                let mut tag = String::new();
+                let mut in_string = false;
+                loop {
+                    match chars.next() {
+                        Some((_, '\\')) if in_string => {
+                            if let Some((_, escaped)) = chars.next() {
+                                tag.push('\\');
+                                tag.push(escaped);
+                            }
+                        }
+                        Some((_, '"')) => {
+                            in_string = !in_string;
+                            tag.push('"');
+                        }
+                        Some((_, ']')) if !in_string => break,
+                        Some((_, c)) => tag.push(c),
+                        None => return Err(PgnError::InvalidTag),
+                    }
+                }
+                game.tags.push(parse_tag(&tag)?);
+            }
+            '{' => {
+                if !chars.by_ref().any(|(_, c)| c == '}') {
+                    return Err(PgnError::UnterminatedComment);
+                }
+            }
+            ';' => {
+                chars.by_ref().find(|(_, c)| *c == '\n');
+            }
+            '(' => skip_variation(&mut chars)?,
+            ')' => return Err(PgnError::UnterminatedVariation),
+            '$' => {
+                while chars.next_if(|(_, c)| c.is_ascii_digit()).is_some() {}
+            }
+            _ => {
+                let mut end = start + c.len_utf8();
+                while let Some((index, c)) = chars.next_if(|(_, c)| !c.is_whitespace() && !"[]{}();$".contains(*c)) {
+                    end = index + c.len_utf8();
+                }
+
+                let token = &contents[start..end];
+                match parse_result(token) {
+                    Some(result) => {
+                        game.result = result;
+                        games.push(game);
+                        game = PgnGame::new();
+                    }
+                    None => {
+                        // "12." and "12..." on their own or in front of the move, as in "1.e4"
+                        let san = token.trim_start_matches(|c: char| c.is_ascii_digit()).trim_start_matches('.');
+                        if !san.is_empty() {
+                            game.moves.push(san.to_string());
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    if !game.moves.is_empty() || !game.tags.is_empty() {
+        games.push(game);
+    }
+
+    Ok(games)
+}
+
+// `Name "value"`, with \" and \\ escapes in the value
+fn parse_tag(tag: &str) -> Result<(String, String), PgnError> {
+    let tag = tag.trim();
+    let (name, value) = tag.split_once(char::is_whitespace).ok_or(PgnError::InvalidTag)?;
+    let value = value.trim();
+    if name.is_empty() || value.len() < 2 || !value.starts_with('"') || !value.ends_with('"') {
+        return Err(PgnError::InvalidTag);
+    }
+
+    let mut unescaped = String::new();
+    let mut escaped = false;
+    for c in value[1..value.len() - 1].chars() {
+        match (escaped, c) {
+            (false, '\\') => escaped = true,
+            _ => {
+                unescaped.push(c);
+                escaped = false;
+            }
+        }
+    }
+
+    Ok((name.to_string(), unescaped))
+}
+
+// the game termination markers, "*" being a game still going on or abandoned
+fn parse_result(token: &str) -> Option<Option<GameResult>> {
+    match token {
+        "1-0" => Some(Some(GameResult::WhiteWins)),
+        "0-1" => Some(Some(GameResult::BlackWins)),
+        "1/2-1/2" => Some(Some(GameResult::Draw)),
+        "*" => Some(None),
+        _ => None,
+    }
+}
+
+// variations nest, and may hold comments with parentheses of their own
+fn skip_variation(chars: &mut impl Iterator<Item = (usize, char)>) -> Result<(), PgnError> {
+    let mut depth = 1;
+    while depth > 0 {
+        match chars.next() {
+            Some((_, '(')) => depth += 1,
+            Some((_, ')')) => depth -= 1,
+            Some((_, '{')) => {
+                if !chars.any(|(_, c)| c == '}') {
+                    return Err(PgnError::UnterminatedComment);
+                }
+            }
+            Some(_) => {}
+            None => return Err(PgnError::UnterminatedVariation),
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,262 @@
+use crate::chessboard::chessboard::Chessboard;
+use crate::chessboard::piece::PieceType;
+use crate::chessboard::player::PlayerColor;
+use crate::constants::BOARD_SIZE;
+use crate::exceptions::SanError;
+use crate::min_max::next_move_generator::generate_next_moves;
+
+// Standard algebraic notation for the side to move of `chessboard`.
+// Promotions are always to a queen, as in Chessboard::perform_move.
+
+pub fn move_to_san(chessboard: &Chessboard, next_move: (u64, u64)) -> Result<String, SanError> {
+    let color = chessboard.side_to_move;
+    let legal_moves = generate_next_moves(chessboard, color);
+    if !legal_moves.contains(&next_move) {
+        return Err(SanError::IllegalMove);
+    }
+
+    let (from, to) = next_move;
+    let piece_type = chessboard.get_piece_type_color(color, from);
+    let mut san = String::new();
+
+    if piece_type == PieceType::King && from.abs_diff(to) == 2 {
+        san.push_str(if to > from { "O-O" } else { "O-O-O" });
+    } else {
+        let is_capture = chessboard.get_piece_type_color(color.opposite(), to) != PieceType::None
+            || (piece_type == PieceType::Pawn && from % BOARD_SIZE != to % BOARD_SIZE);
+
+        if piece_type == PieceType::Pawn {
+            if is_capture {
+                san.push(file_char(from));
+            }
+        } else {
+            san.push(piece_char(piece_type));
+            san.push_str(&disambiguation(
+                chessboard,
+                &legal_moves,
+                next_move,
+                piece_type,
+            ));
+        }
+
+        if is_capture {
+            san.push('x');
+        }
+        san.push_str(&Chessboard::convert_index_to_square(to));
+
+        if piece_type == PieceType::Pawn
+            && (to / BOARD_SIZE == 0 || to / BOARD_SIZE == BOARD_SIZE - 1)
+        {
+            san.push_str("=Q");
+        }
+    }
+
+    let mut next_state = chessboard.clone();
+    let from_str = Chessboard::convert_index_to_square(from);
+    let to_str = Chessboard::convert_index_to_square(to);
+    next_state
+        .perform_move(&from_str, &to_str, color)
+        .map_err(|_| SanError::IllegalMove)?;
+
+    if next_state.is_in_check(color.opposite()) {
+        if generate_next_moves(&next_state, color.opposite()).is_empty() {
+            san.push('#');
+        } else {
+            san.push('+');
+        }
+    }
+
+    Ok(san)
+}
+
+pub fn parse_san(chessboard: &Chessboard, san: &str) -> Result<(u64, u64), SanError> {
+    let color = chessboard.side_to_move;
+    let legal_moves = generate_next_moves(chessboard, color);
+    let san = san.trim().trim_end_matches(['+', '#', '!', '?']);
+
+    let castling = match (san, color) {
+        ("O-O" | "0-0", PlayerColor::White) => Some((4, 6)),
+        ("O-O-O" | "0-0-0", PlayerColor::White) => Some((4, 2)),
+        ("O-O" | "0-0", PlayerColor::Black) => Some((60, 62)),
+        ("O-O-O" | "0-0-0", PlayerColor::Black) => Some((60, 58)),
+        _ => None,
+    };
+    if let Some(castling) = castling {
+        return if legal_moves.contains(&castling) {
+            Ok(castling)
+        } else {
+            Err(SanError::IllegalMove)
+        };
+    }
+
+    if !san.is_ascii() || san.len() < 2 {
+        return Err(SanError::InvalidSyntax);
+    }
+
+    // "e8=Q" or "e8Q"
+    let san = match san.find('=') {
+        Some(index) => check_promotion(&san[..index], &san[index + 1..])?,
+        None if san.len() > 2
+            && san.as_bytes()[san.len() - 2].is_ascii_digit()
+            && san.as_bytes()[san.len() - 1].is_ascii_uppercase() =>
+        {
+            check_promotion(&san[..san.len() - 1], &san[san.len() - 1..])?
+        }
+        None => san,
+    };
+
+    let (piece_type, body) = match san.chars().next().and_then(piece_from_char) {
+        Some(piece_type) => (piece_type, &san[1..]),
+        None => (PieceType::Pawn, san),
+    };
+
+    let is_capture = body.contains('x');
+    let body: String = body.chars().filter(|c| *c != 'x').collect();
+    if body.len() < 2 || body.len() > 4 {
+        return Err(SanError::InvalidSyntax);
+    }
+
+    let to = Chessboard::parse_square(&body[body.len() - 2..]).ok_or(SanError::InvalidSyntax)?;
+    let mut from_file = None;
+    let mut from_rank = None;
+    for c in body[..body.len() - 2].chars() {
+        match c {
+            'a'..='h' => from_file = Some(c as u64 - 'a' as u64),
+            '1'..='8' => from_rank = Some(c as u64 - '1' as u64),
+            _ => return Err(SanError::InvalidSyntax),
+        }
+    }
+
+    if piece_type == PieceType::Pawn && !is_capture {
+        from_file = Some(to % BOARD_SIZE);
+    }
+
+    let candidates: Vec<(u64, u64)> = legal_moves
+        .into_iter()
+        .filter(|(from, move_to)| {
+            *move_to == to
+                && chessboard.get_piece_type_color(color, *from) == piece_type
+                && from_file.is_none_or(|file| from % BOARD_SIZE == file)
+                && from_rank.is_none_or(|rank| from / BOARD_SIZE == rank)
+        })
+        .collect();
+
+    match candidates.len() {
+        0 => Err(SanError::IllegalMove),
+        1 => Ok(candidates[0]),
+        _ => Err(SanError::AmbiguousMove),
+    }
+}
+
+// plays a sequence of SAN moves, returning them as (from, to) pairs
+pub fn parse_san_line(chessboard: &Chessboard, line: &[&str]) -> Result<Vec<(u64, u64)>, SanError> {
+    let mut state = chessboard.clone();
+    let mut moves = vec![];
+
+    for san in line {
+        let next_move = parse_san(&state, san)?;
+        let color = state.side_to_move;
+        let from = Chessboard::convert_index_to_square(next_move.0);
+        let to = Chessboard::convert_index_to_square(next_move.1);
+        state
+            .perform_move(&from, &to, color)
+            .map_err(|_| SanError::IllegalMove)?;
+        moves.push(next_move);
+    }
+
+    Ok(moves)
+}
+
+pub fn line_to_san(chessboard: &Chessboard, line: &[(u64, u64)]) -> Result<Vec<String>, SanError> {
+    let mut state = chessboard.clone();
+    let mut result = vec![];
+
+    for next_move in line {
+        result.push(move_to_san(&state, *next_move)?);
+        let color = state.side_to_move;
+        let from = Chessboard::convert_index_to_square(next_move.0);
+        let to = Chessboard::convert_index_to_square(next_move.1);
+        state
+            .perform_move(&from, &to, color)
+            .map_err(|_| SanError::IllegalMove)?;
+    }
+
+    Ok(result)
+}
+
+fn check_promotion<'a>(san: &'a str, promotion: &str) -> Result<&'a str, SanError> {
+    match promotion {
+        "Q" => Ok(san),
+        "R" | "B" | "N" => Err(SanError::UnsupportedPromotion),
+        _ => Err(SanError::InvalidSyntax),
+    }
+}
+
+fn disambiguation(
+    chessboard: &Chessboard,
+    legal_moves: &[(u64, u64)],
+    next_move: (u64, u64),
+    piece_type: PieceType,
+) -> String {
+    let (from, to) = next_move;
+    let color = chessboard.side_to_move;
+    let others: Vec<u64> = legal_moves
+        .iter()
+        .filter(|(other_from, other_to)| {
+            *other_to == to
+                && *other_from != from
+                && chessboard.get_piece_type_color(color, *other_from) == piece_type
+        })
+        .map(|(other_from, _)| *other_from)
+        .collect();
+
+    if others.is_empty() {
+        return String::new();
+    }
+
+    if others
+        .iter()
+        .all(|other| other % BOARD_SIZE != from % BOARD_SIZE)
+    {
+        return file_char(from).to_string();
+    }
+
+    if others
+        .iter()
+        .all(|other| other / BOARD_SIZE != from / BOARD_SIZE)
+    {
+        return rank_char(from).to_string();
+    }
+
+    Chessboard::convert_index_to_square(from)
+}
+
+fn file_char(index: u64) -> char {
+    (b'a' + (index % BOARD_SIZE) as u8) as char
+}
+
+fn rank_char(index: u64) -> char {
+    (b'1' + (index / BOARD_SIZE) as u8) as char
+}
+
+fn piece_char(piece_type: PieceType) -> char {
+    match piece_type {
+        PieceType::Knight => 'N',
+        PieceType::Bishop => 'B',
+        PieceType::Rook => 'R',
+        PieceType::Queen => 'Q',
+        PieceType::King => 'K',
+        PieceType::Pawn | PieceType::None => ' ',
+    }
+}
+
+fn piece_from_char(c: char) -> Option<PieceType> {
+    match c {
+        'N' => Some(PieceType::Knight),
+        'B' => Some(PieceType::Bishop),
+        'R' => Some(PieceType::Rook),
+        'Q' => Some(PieceType::Queen),
+        'K' => Some(PieceType::King),
+        _ => None,
+    }
+}
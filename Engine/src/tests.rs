@@ -1,8 +1,43 @@
 #[cfg(test)]
 mod tests {
+    use std::time::{Duration, Instant};
     use crate::chessboard::chessboard::Chessboard;
-    use crate::exceptions::MoveError;
+    use crate::chessboard::fen::STARTING_FEN;
+    use crate::chessboard::packed::{PackedPosition, PACKED_POSITION_SIZE};
+    use crate::exceptions::{BookError, EpdError, FenError, MoveError, OptionError, PackedPositionError, PgnError, SanError};
+    #[cfg(feature = "syzygy")]
+    use crate::exceptions::TablebaseError;
+    use crate::min_max::min_max::{get_best_move, search, search_with_skill, SearchLine};
+    use crate::evaluator::{evaluate, KNIGHT_VALUE, PAWN_VALUE, QUEEN_VALUE, ROOK_VALUE};
+    use crate::min_max::move_ordering::MoveOrderer;
+    use crate::min_max::next_move_generator::generate_capture_moves;
+    use crate::min_max::benchmark::{signature, time_to_depth, SIGNATURE_DEPTH};
+    use crate::min_max::extensions::is_passed_pawn_push;
+    use crate::min_max::pruning::{has_non_pawn_material, ReductionTable};
+    use crate::min_max::search_limits::{SearchLimits, StopFlag};
+    use crate::min_max::search_info::SearchInfo;
+    use crate::min_max::search_options::SearchOptions;
+    use crate::min_max::search_stats::SearchStats;
+    use crate::min_max::quiescence::quiescence_search;
+    use crate::min_max::time_manager::{TimeControl, TimeManager};
+    use crate::min_max::score::{Score, MATE_BOUND};
+    use crate::min_max::skill::{level_from_elo, limit_search, pick_line, MAX_SKILL_LEVEL, SKILL_MAX_ELO, SKILL_MIN_ELO};
+    use crate::min_max::transposition_table::{Bound, TranspositionTable};
+    use crate::notation::epd::{parse_epd_file, Epd};
+    use crate::notation::book_builder::{BookBuilder, BookFilters};
+    use crate::notation::pgn::parse_pgn;
+    use crate::notation::polyglot::{decode_move, encode_move, BookEntry, BookSelection, OpeningBook, POLYGLOT_ENTRY_SIZE};
+    use crate::notation::san::{move_to_san, parse_san};
     use crate::chessboard::player::PlayerColor;
+    use crate::chessboard::game::GameResult;
+    use crate::chessboard::piece::is_king_move_blocked;
+    use crate::min_max::next_move_generator::generate_next_moves;
+    #[cfg(feature = "serde")]
+    use crate::chessboard::game::GameRecord;
+    #[cfg(feature = "serde")]
+    use crate::chessboard::piece::PieceType;
+    #[cfg(feature = "syzygy")]
+    use crate::min_max::syzygy::Tablebase;
 
     #[test]
     fn test_chessboard_valid_moves() {
@@ -228,7 +263,7 @@ mod tests {
             Ok(())
         );
         assert_eq!(
-            chessboard.perform_move("d7", "d5", PlayerColor::Black),
+            chessboard.perform_move("b7", "b6", PlayerColor::Black),
             Ok(())
         );
 
@@ -237,7 +272,7 @@ mod tests {
             Ok(())
         );
         assert_eq!(
-            chessboard.perform_move("b7", "b6", PlayerColor::Black),
+            chessboard.perform_move("d7", "d5", PlayerColor::Black),
             Ok(())
         );
 
@@ -379,5 +414,1583 @@ mod tests {
             chessboard.perform_move("d6", "e5", PlayerColor::Black),
             Ok(())
         );
+
+        // en passant only right after the double push, and not past a pawn that came one square at a time
+        let mut chessboard = Chessboard::new();
+        for (from, to, color) in [
+            ("e2", "e4", PlayerColor::White),
+            ("a7", "a6", PlayerColor::Black),
+            ("e4", "e5", PlayerColor::White),
+            ("d7", "d5", PlayerColor::Black),
+            ("h2", "h3", PlayerColor::White),
+            ("f7", "f6", PlayerColor::Black),
+            ("h3", "h4", PlayerColor::White),
+            ("f6", "f5", PlayerColor::Black),
+        ] {
+            assert_eq!(chessboard.perform_move(from, to, color), Ok(()));
+        }
+        assert_eq!(chessboard.en_passant, None);
+        assert_eq!(
+            chessboard.clone().perform_move("e5", "d6", PlayerColor::White),
+            Err(MoveError::InvalidMove)
+        );
+        assert_eq!(
+            chessboard.clone().perform_move("e5", "f6", PlayerColor::White),
+            Err(MoveError::InvalidMove)
+        );
+        let chessboard = Chessboard::from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - - 0 1").unwrap();
+        assert_eq!(
+            chessboard.clone().perform_move("e5", "d6", PlayerColor::White),
+            Err(MoveError::InvalidMove)
+        );
+        let chessboard = Chessboard::from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1").unwrap();
+        assert_eq!(
+            chessboard.clone().perform_move("e5", "d6", PlayerColor::White),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_king_and_pawn_rules() {
+        println!("\n*******************\nRunning test_king_and_pawn_rules()\n*******************\n");
+
+        // a pawn attacks diagonally only, the king may step in front of it or beside it
+        let chessboard = Chessboard::from_fen("4k3/8/8/8/3p4/8/4K3/8 w - - 0 1").unwrap();
+        assert_eq!(chessboard.clone().perform_move("e2", "d3", PlayerColor::White), Ok(()));
+        assert_eq!(chessboard.clone().perform_move("e2", "e3", PlayerColor::White), Err(MoveError::InvalidMove));
+        let chessboard = Chessboard::from_fen("4k3/8/8/8/3p4/5K2/8/8 w - - 0 1").unwrap();
+        assert_eq!(chessboard.clone().perform_move("f3", "e4", PlayerColor::White), Ok(()));
+        assert_eq!(chessboard.clone().perform_move("f3", "e3", PlayerColor::White), Err(MoveError::InvalidMove));
+        // the same before the move is made, the opponent's pawns attacking towards the king
+        let blocked = |to| is_king_move_blocked(to, PlayerColor::White, chessboard.get_board(), chessboard.white.clone(), chessboard.black.clone());
+        assert!(blocked(20) && blocked(18));
+        assert!(!blocked(19) && !blocked(28));
+        let chessboard = Chessboard::from_fen("4k3/4P3/8/8/8/8/8/4K3 b - - 0 1").unwrap();
+        assert_eq!(chessboard.clone().perform_move("e8", "d8", PlayerColor::Black), Err(MoveError::InvalidMove));
+        assert_eq!(chessboard.clone().perform_move("e8", "e7", PlayerColor::Black), Ok(()));
+
+        // the square a pawn pushes to is not in check
+        assert!(!Chessboard::from_fen("4k3/8/8/8/3p4/3K4/8/8 w - - 0 1").unwrap().is_in_check(PlayerColor::White));
+        assert!(Chessboard::from_fen("4k3/8/8/8/3p4/4K3/8/8 w - - 0 1").unwrap().is_in_check(PlayerColor::White));
+        assert!(!Chessboard::from_fen("8/8/8/8/8/3k4/3P4/4K3 b - - 0 1").unwrap().is_in_check(PlayerColor::Black));
+        assert!(Chessboard::from_fen("8/8/8/8/8/2k5/3P4/4K3 b - - 0 1").unwrap().is_in_check(PlayerColor::Black));
+
+        // double pushes from the starting rank only, and not through a piece
+        let moves = generate_next_moves(&Chessboard::new(), PlayerColor::White);
+        assert!(moves.contains(&(12, 28)) && moves.contains(&(8, 24)));
+        assert_eq!(moves.len(), 20);
+        let moves = generate_next_moves(&Chessboard::from_fen("4k3/3p4/8/8/4n3/3P4/4P3/4K3 w - - 0 1").unwrap(), PlayerColor::White);
+        assert!(moves.contains(&(12, 20)) && !moves.contains(&(12, 28)) && !moves.contains(&(19, 35)));
+        let moves = generate_next_moves(&Chessboard::from_fen("4k3/3p4/8/8/8/8/8/4K3 b - - 0 1").unwrap(), PlayerColor::Black);
+        assert!(moves.contains(&(51, 43)) && moves.contains(&(51, 35)));
+
+        // castling on both sides while the rights last
+        let chessboard = Chessboard::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+        let moves = generate_next_moves(&chessboard, PlayerColor::White);
+        assert!(moves.contains(&(4, 6)) && moves.contains(&(4, 2)));
+        let moves = generate_next_moves(&chessboard, PlayerColor::Black);
+        assert!(moves.contains(&(60, 62)) && moves.contains(&(60, 58)));
+        let moves = generate_next_moves(&Chessboard::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w Kq - 0 1").unwrap(), PlayerColor::White);
+        assert!(moves.contains(&(4, 6)) && !moves.contains(&(4, 2)));
+        let moves = generate_next_moves(&Chessboard::from_fen("r3k2r/8/8/8/8/8/8/R3K2R b Kq - 0 1").unwrap(), PlayerColor::Black);
+        assert!(!moves.contains(&(60, 62)) && moves.contains(&(60, 58)));
+    }
+
+    #[test]
+    fn test_fen() {
+        println!("\n*******************\nRunning test_fen()\n*******************\n");
+
+        let mut chessboard = Chessboard::new();
+        assert_eq!(chessboard.to_fen(), STARTING_FEN);
+
+        assert_eq!(
+            chessboard.perform_move("e2", "e4", PlayerColor::White),
+            Ok(())
+        );
+        assert_eq!(
+            chessboard.to_fen(),
+            "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1"
+        );
+
+        assert_eq!(
+            chessboard.perform_move("g8", "f6", PlayerColor::Black),
+            Ok(())
+        );
+        assert_eq!(
+            chessboard.perform_move("e1", "e2", PlayerColor::White),
+            Ok(())
+        );
+        assert_eq!(
+            chessboard.to_fen(),
+            "rnbqkb1r/pppppppp/5n2/8/4P3/8/PPPPKPPP/RNBQ1BNR b kq - 2 2"
+        );
+
+        let fen = "r3k2r/8/8/3pP3/8/8/8/R3K2R w Kq d6 3 20";
+        assert_eq!(Chessboard::from_fen(fen).unwrap().to_fen(), fen);
+
+        assert_eq!(
+            Chessboard::from_fen("8/8/8/8/8/8/8/8 w - -").unwrap_err(),
+            FenError::InvalidPiecePlacement
+        );
+        // empty squares are counted by a single digit from 1 to 8
+        assert_eq!(
+            Chessboard::from_fen("4k3/8/8/8/8/8/0PPPPPPPP/4K3 w - - 0 1").unwrap_err(),
+            FenError::InvalidPiecePlacement
+        );
+        assert_eq!(
+            Chessboard::from_fen("4k3/8/8/8/8/8/9/4K3 w - - 0 1").unwrap_err(),
+            FenError::InvalidPiecePlacement
+        );
+        assert_eq!(
+            Chessboard::from_fen("4k3/8/8/44/8/8/8/4K3 w - - 0 1").unwrap_err(),
+            FenError::InvalidPiecePlacement
+        );
+        assert_eq!(
+            Chessboard::from_fen("4k3/8/8/8/8/8/8/4K12 w - - 0 1").unwrap_err(),
+            FenError::InvalidPiecePlacement
+        );
+        assert_eq!(
+            Chessboard::from_fen("4k3/8/8/8/8/8/8/4K3 x - - 0 1").unwrap_err(),
+            FenError::InvalidSideToMove
+        );
+        assert_eq!(
+            Chessboard::from_fen("4k3/8/8/8/8/8/8/4K3 w - e4 0 1").unwrap_err(),
+            FenError::InvalidEnPassant
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        println!("\n*******************\nRunning test_serde()\n*******************\n");
+
+        let mut record = GameRecord::new(Chessboard::new());
+        record.push_move((12, 28));
+        record.push_move((52, 36));
+        record.result = Some(GameResult::Draw);
+
+        let json = serde_json::to_string(&record).unwrap();
+        assert_eq!(
+            json,
+            format!(
+                "{{\"start\":\"{}\",\"moves\":[\"e2e4\",\"e7e5\"],\"result\":\"1/2-1/2\"}}",
+                STARTING_FEN
+            )
+        );
+
+        let decoded: GameRecord = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded.moves, record.moves);
+        assert_eq!(decoded.result, record.result);
+        assert_eq!(
+            decoded.replay().unwrap().to_fen(),
+            "rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq e6 0 2"
+        );
+
+        assert_eq!(serde_json::to_string(&PlayerColor::Black).unwrap(), "\"Black\"");
+        assert_eq!(serde_json::to_string(&PieceType::Queen).unwrap(), "\"Queen\"");
+        assert!(serde_json::from_str::<Chessboard>("\"not a fen\"").is_err());
+    }
+
+    #[test]
+    fn test_packed_position() {
+        println!("\n*******************\nRunning test_packed_position()\n*******************\n");
+
+        let fens = [
+            STARTING_FEN,
+            "r3k2r/8/8/3pP3/8/8/8/R3K2R w Kq d6 3 20",
+            "8/2k5/8/8/8/8/5K2/8 b - - 99 300",
+            "rnbqkbnr/pp1ppppp/8/2p5/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 1 2",
+        ];
+
+        for fen in fens {
+            let chessboard = Chessboard::from_fen(fen).unwrap();
+            let packed = chessboard.to_packed().unwrap();
+            assert_eq!(packed.as_bytes().len(), PACKED_POSITION_SIZE);
+            assert_eq!(Chessboard::from_packed(&packed).unwrap().to_fen(), fen);
+        }
+
+        // castling history is kept even when FEN could not tell it apart
+        let mut chessboard = Chessboard::new();
+        assert_eq!(chessboard.perform_move("g1", "f3", PlayerColor::White), Ok(()));
+        assert_eq!(chessboard.perform_move("b8", "c6", PlayerColor::Black), Ok(()));
+        assert_eq!(chessboard.perform_move("h1", "g1", PlayerColor::White), Ok(()));
+        assert_eq!(chessboard.perform_move("c6", "b8", PlayerColor::Black), Ok(()));
+        assert_eq!(chessboard.perform_move("g1", "h1", PlayerColor::White), Ok(()));
+        assert_eq!(chessboard.perform_move("e7", "e5", PlayerColor::Black), Ok(()));
+
+        let decoded = Chessboard::from_packed(&chessboard.to_packed().unwrap()).unwrap();
+        assert_eq!(decoded.to_fen(), chessboard.to_fen());
+        assert_eq!(decoded.get_board(), chessboard.get_board());
+        assert!(decoded.white.has_right_rook_moved);
+        assert!(!decoded.white.has_left_rook_moved);
+        assert!(!decoded.white.has_king_moved);
+        assert_eq!(decoded.en_passant, Some(44));
+
+        let mut bytes = *chessboard.to_packed().unwrap().as_bytes();
+        bytes[8] = 0xCC;
+        assert_eq!(
+            Chessboard::from_packed(&PackedPosition::from_bytes(bytes)).unwrap_err(),
+            PackedPositionError::InvalidPieceCode
+        );
+
+        // the white king on e1 is the fifth piece, a queen there leaves white without one
+        let mut bytes = *Chessboard::new().to_packed().unwrap().as_bytes();
+        bytes[10] = (bytes[10] & 0xF0) | 4;
+        assert_eq!(
+            Chessboard::from_packed(&PackedPosition::from_bytes(bytes)).unwrap_err(),
+            PackedPositionError::InvalidKingCount
+        );
+        // and a king on d1 gives it two
+        bytes[9] = (bytes[9] & 0x0F) | (5 << 4);
+        bytes[10] = (bytes[10] & 0xF0) | 5;
+        assert_eq!(
+            Chessboard::from_packed(&PackedPosition::from_bytes(bytes)).unwrap_err(),
+            PackedPositionError::InvalidKingCount
+        );
+    }
+
+    #[test]
+    fn test_san() {
+        println!("\n*******************\nRunning test_san()\n*******************\n");
+
+        let chessboard = Chessboard::new();
+        assert_eq!(move_to_san(&chessboard, (12, 28)), Ok("e4".to_string()));
+        assert_eq!(move_to_san(&chessboard, (6, 21)), Ok("Nf3".to_string()));
+        assert_eq!(parse_san(&chessboard, "e4"), Ok((12, 28)));
+        assert_eq!(parse_san(&chessboard, "Nc3"), Ok((1, 18)));
+        assert_eq!(parse_san(&chessboard, "Ke2"), Err(SanError::IllegalMove));
+        assert_eq!(parse_san(&chessboard, "Zz9"), Err(SanError::InvalidSyntax));
+
+        let chessboard = Chessboard::from_fen("4k3/8/8/8/8/8/8/1N2KN2 w - - 0 1").unwrap();
+        assert_eq!(move_to_san(&chessboard, (1, 11)), Ok("Nbd2".to_string()));
+        assert_eq!(parse_san(&chessboard, "Nfd2"), Ok((5, 11)));
+        assert_eq!(parse_san(&chessboard, "Nd2"), Err(SanError::AmbiguousMove));
+
+        let chessboard = Chessboard::from_fen("7k/8/8/R7/8/8/8/R3K3 w - - 0 1").unwrap();
+        assert_eq!(move_to_san(&chessboard, (0, 16)), Ok("R1a3".to_string()));
+        assert_eq!(parse_san(&chessboard, "R5a3"), Ok((32, 16)));
+
+        let chessboard = Chessboard::from_fen("r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 0 1").unwrap();
+        assert_eq!(move_to_san(&chessboard, (60, 62)), Ok("O-O".to_string()));
+        assert_eq!(parse_san(&chessboard, "O-O-O"), Ok((60, 58)));
+
+        let chessboard = Chessboard::from_fen("6k1/5ppp/8/8/8/8/8/R3K3 w - - 0 1").unwrap();
+        assert_eq!(move_to_san(&chessboard, (0, 56)), Ok("Ra8#".to_string()));
+
+        let chessboard = Chessboard::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 0 1").unwrap();
+        assert_eq!(move_to_san(&chessboard, (0, 56)), Ok("Ra8+".to_string()));
+
+        let chessboard = Chessboard::from_fen("4k3/1P6/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        assert_eq!(move_to_san(&chessboard, (49, 57)), Ok("b8=Q+".to_string()));
+        assert_eq!(parse_san(&chessboard, "b8=Q"), Ok((49, 57)));
+        assert_eq!(parse_san(&chessboard, "b8=N"), Err(SanError::UnsupportedPromotion));
+    }
+
+    #[test]
+    fn test_epd() {
+        println!("\n*******************\nRunning test_epd()\n*******************\n");
+
+        let line = "2rr3k/pp3pp1/1nnqbN1p/3pN3/2pP4/2P3Q1/PPB4P/R4RK1 w - - bm Qg6; id \"WAC.001\";";
+        let epd = Epd::parse(line).unwrap();
+        assert_eq!(epd.id(), Some("WAC.001"));
+        assert_eq!(epd.best_moves(), Ok(vec![(22, 46)]));
+        assert_eq!(epd.avoid_moves(), Ok(vec![]));
+        assert_eq!(epd.to_string(), line);
+
+        let epd = Epd::parse(
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - am f3 g4; c0 \"a; b\"; acd 12; ce -35; pv e4 e5 Nf3; hmvc 4; fmvn 9",
+        )
+        .unwrap();
+        assert_eq!(epd.avoid_moves(), Ok(vec![(13, 21), (14, 30)]));
+        assert_eq!(epd.comment(0), Some("a; b"));
+        assert_eq!(epd.analysis_depth(), Some(12));
+        assert_eq!(epd.centipawn_evaluation(), Some(-35));
+        assert_eq!(epd.direct_mate(), None);
+        assert_eq!(epd.principal_variation(), Ok(vec![(12, 28), (52, 36), (6, 21)]));
+        assert_eq!(epd.position.halfmove_clock, 4);
+        assert_eq!(epd.position.fullmove_number, 9);
+
+        let mut epd = Epd::new(Chessboard::from_fen("6k1/5ppp/8/8/8/8/8/R3K3 w - - 0 1").unwrap());
+        epd.set_moves("bm", &[(0, 56)]).unwrap();
+        epd.set_operation("dm", vec!["1".to_string()]);
+        epd.set_operation("id", vec!["back rank".to_string()]);
+        assert_eq!(epd.direct_mate(), Some(1));
+        assert_eq!(
+            epd.to_string(),
+            "6k1/5ppp/8/8/8/8/8/R3K3 w - - bm Ra8#; dm 1; id \"back rank\";"
+        );
+
+        // quotes and backslashes in strings are escaped, and read back
+        let mut quoted = epd.clone();
+        quoted.set_operation("c0", vec!["say \"mate\" \\ now".to_string()]);
+        assert!(quoted.to_string().ends_with("c0 \"say \\\"mate\\\" \\\\ now\";"));
+        assert_eq!(Epd::parse(&quoted.to_string()).unwrap().comment(0), Some("say \"mate\" \\ now"));
+
+        let records = parse_epd_file(&format!("# comment\n\n{}\n{}\n", line, epd)).unwrap();
+        assert_eq!(records.len(), 2);
+
+        assert_eq!(
+            Epd::parse("8/8/8/8 w").unwrap_err(),
+            EpdError::InvalidPosition(FenError::WrongFieldCount)
+        );
+        assert_eq!(
+            Epd::parse("4k3/8/8/8/8/8/8/4K3 w - - id \"unterminated").unwrap_err(),
+            EpdError::UnterminatedString
+        );
+        assert_eq!(
+            Epd::parse("4k3/8/8/8/8/8/8/4K3 w - - bm Qh5;").unwrap().best_moves(),
+            Err(EpdError::InvalidMove(SanError::IllegalMove))
+        );
+    }
+
+    #[test]
+    fn test_time_manager() {
+        println!("\n*******************\nRunning test_time_manager()\n*******************\n");
+
+        let time_manager = TimeManager::new(&TimeControl::default());
+        assert!(!time_manager.is_limited());
+        assert!(time_manager.should_start_iteration());
+        assert!(!time_manager.is_time_up());
+
+        let time_manager = TimeManager::new(&TimeControl::with_move_time(Duration::from_millis(1030)));
+        assert_eq!(time_manager.soft_limit(), Some(Duration::from_millis(1000)));
+        assert_eq!(time_manager.hard_limit(), Some(Duration::from_millis(1000)));
+
+        // 60s / 30 moves + 3/4 of the increment, hard limit four times that
+        let time_manager = TimeManager::new(&TimeControl::with_clock(
+            Duration::from_millis(60_030),
+            Duration::from_millis(1000),
+            None,
+        ));
+        assert_eq!(time_manager.soft_limit(), Some(Duration::from_millis(2750)));
+        assert_eq!(time_manager.hard_limit(), Some(Duration::from_millis(11_000)));
+
+        // never more than a third of the clock
+        let time_manager = TimeManager::new(&TimeControl::with_clock(
+            Duration::from_millis(3030),
+            Duration::from_millis(5000),
+            Some(1),
+        ));
+        assert_eq!(time_manager.soft_limit(), Some(Duration::from_millis(1000)));
+        assert_eq!(time_manager.hard_limit(), Some(Duration::from_millis(1000)));
+
+        let time_manager = TimeManager::new(&TimeControl::with_move_time(Duration::ZERO));
+        assert!(!time_manager.should_start_iteration());
+        assert!(time_manager.is_time_up());
+    }
+
+    #[test]
+    fn test_iterative_deepening() {
+        println!("\n*******************\nRunning test_iterative_deepening()\n*******************\n");
+
+        let chessboard = Chessboard::from_fen("6k1/5ppp/8/8/8/8/8/R3K3 w - - 0 1").unwrap();
+        let limits = SearchLimits::move_time(Duration::from_millis(500));
+
+        let transposition_table = TranspositionTable::new(1);
+        let move_orderer = MoveOrderer::new();
+
+        let start = Instant::now();
+        let best_move = get_best_move(
+            &chessboard,
+            &limits,
+            &SearchOptions::default(),
+            &transposition_table,
+            &move_orderer,
+            &StopFlag::new(),
+            None,
+        );
+        assert_eq!(best_move, (0, 56));
+        assert!(start.elapsed() < Duration::from_millis(1500));
+    }
+
+    #[test]
+    fn test_principal_variation_search() {
+        println!("\n*******************\nRunning test_principal_variation_search()\n*******************\n");
+
+        let options = SearchOptions::default();
+        let chessboard = Chessboard::from_fen("6k1/5ppp/8/8/8/8/8/R3K3 w - - 0 1").unwrap();
+        let limits = SearchLimits::move_time(Duration::from_millis(200));
+        let result = search(&chessboard, &limits, &options, &TranspositionTable::new(1), &MoveOrderer::new(), &StopFlag::new(), None);
+        assert_eq!(result.score, Score::mate_in(1));
+        assert_eq!(result.principal_variation, vec![(0, 56)]);
+
+        // mate in two, found the same way every time without a clock
+        let chessboard = Chessboard::from_fen("k7/8/2K5/8/8/8/8/7R w - - 0 1").unwrap();
+        let limits = SearchLimits::default();
+        let first = search(&chessboard, &limits, &options, &TranspositionTable::new(1), &MoveOrderer::new(), &StopFlag::new(), None);
+        let second = search(&chessboard, &limits, &options, &TranspositionTable::new(1), &MoveOrderer::new(), &StopFlag::new(), None);
+        assert_eq!(first.score, Score::mate_in(3));
+        assert_eq!(first.principal_variation.len(), 3);
+        assert_eq!(first.best_move, first.principal_variation[0]);
+        assert_eq!((first.depth, &first.lines), (second.depth, &second.lines));
+        assert_eq!(first.stats.total_nodes(), second.stats.total_nodes());
+    }
+
+    #[test]
+    fn test_zobrist_hash() {
+        println!("\n*******************\nRunning test_zobrist_hash()\n*******************\n");
+
+        let mut chessboard = Chessboard::new();
+        let start_hash = chessboard.zobrist_hash();
+        assert_eq!(start_hash, Chessboard::from_fen(STARTING_FEN).unwrap().zobrist_hash());
+
+        // same position through a transposition
+        let mut transposed = Chessboard::new();
+        for (from, to) in [("g1", "f3"), ("g8", "f6"), ("b1", "c3"), ("b8", "c6")] {
+            let color = chessboard.side_to_move;
+            assert_eq!(chessboard.perform_move(from, to, color), Ok(()));
+        }
+        for (from, to) in [("b1", "c3"), ("b8", "c6"), ("g1", "f3"), ("g8", "f6")] {
+            let color = transposed.side_to_move;
+            assert_eq!(transposed.perform_move(from, to, color), Ok(()));
+        }
+        assert_eq!(chessboard.zobrist_hash(), transposed.zobrist_hash());
+        assert_ne!(chessboard.zobrist_hash(), start_hash);
+
+        // side to move, castling and en passant are part of the key
+        let hashes = [
+            "4k3/8/8/8/8/8/8/R3K2R w KQ - 0 1",
+            "4k3/8/8/8/8/8/8/R3K2R b KQ - 0 1",
+            "4k3/8/8/8/8/8/8/R3K2R w K - 0 1",
+            "4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1",
+            "4k3/8/8/3pP3/8/8/8/4K3 w - - 0 1",
+        ]
+        .map(|fen| Chessboard::from_fen(fen).unwrap().zobrist_hash());
+        for i in 0..hashes.len() {
+            for j in i + 1..hashes.len() {
+                assert_ne!(hashes[i], hashes[j]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_transposition_table() {
+        println!("\n*******************\nRunning test_transposition_table()\n*******************\n");
+
+        let transposition_table = TranspositionTable::new(1);
+        assert_eq!(transposition_table.num_entries(), 65536);
+        assert_eq!(transposition_table.probe(42, 0), None);
+
+        transposition_table.store(42, 5, Bound::Lower, Score::centipawns(-120), Some((12, 28)), 3);
+        let entry = transposition_table.probe(42, 3).unwrap();
+        assert_eq!(entry.depth, 5);
+        assert_eq!(entry.bound, Bound::Lower);
+        assert_eq!(entry.score, Score::centipawns(-120));
+        assert_eq!(entry.best_move, Some((12, 28)));
+
+        // a different key in the same slot misses
+        assert_eq!(transposition_table.probe(42 + 65536, 3), None);
+
+        // shallower results don't replace deeper ones from the same search, but keep the move
+        transposition_table.store(42, 2, Bound::Upper, Score::centipawns(7), None, 3);
+        assert_eq!(transposition_table.probe(42, 3).unwrap().depth, 5);
+        transposition_table.store(42, 6, Bound::Upper, Score::centipawns(7), None, 3);
+        let entry = transposition_table.probe(42, 3).unwrap();
+        assert_eq!(entry.depth, 6);
+        assert_eq!(entry.best_move, Some((12, 28)));
+
+        // mate in 2 plies found at ply 4 is stored as mate from the node
+        // and read back as mate in 2 plies from wherever it is probed
+        transposition_table.store(7, 3, Bound::Exact, Score::mate_in(6), None, 4);
+        assert_eq!(transposition_table.probe(7, 4).unwrap().score, Score::mate_in(6));
+        assert_eq!(transposition_table.probe(7, 1).unwrap().score, Score::mate_in(3));
+        transposition_table.store(8, 3, Bound::Exact, Score::mated_in(6), None, 4);
+        assert_eq!(transposition_table.probe(8, 10).unwrap().score, Score::mated_in(12));
+
+        transposition_table.new_search();
+        assert_eq!(transposition_table.hashfull(), 0);
+        transposition_table.clear();
+        assert_eq!(transposition_table.probe(42, 3), None);
+    }
+
+    #[test]
+    fn test_move_ordering() {
+        println!("\n*******************\nRunning test_move_ordering()\n*******************\n");
+
+        // white: queen d1 can take the rook d8 or the pawn d5, knight c3 can take d5 too
+        let chessboard = Chessboard::from_fen("3r2k1/8/8/3p4/8/2N5/8/3QK3 w - - 0 1").unwrap();
+        let move_orderer = MoveOrderer::new();
+        let quiet = (4, 12);
+        let killer = (18, 35);
+
+        let mut moves = vec![quiet, (3, 35), killer, (18, 35 - 2), (3, 59), (3, 11)];
+        move_orderer.order_moves(&chessboard, &mut moves, PlayerColor::White, Some(quiet), 2, None);
+        // hash move, rook capture, then the pawn capture; the queen taking the pawn the
+        // rook defends loses on the exchange and comes after the quiet moves
+        assert_eq!(moves[0], quiet);
+        assert_eq!(moves[1], (3, 59));
+        assert_eq!(moves[2], (18, 35));
+        assert_eq!(moves[5], (3, 35));
+
+        move_orderer.record_cutoff(&chessboard, PlayerColor::White, (18, 33), 3, &[(3, 11)], 4, 2, Some((60, 52)));
+        assert_eq!(move_orderer.cutoffs(), 1);
+        assert_eq!(move_orderer.first_move_cutoff_rate(), 0.0);
+
+        // the quiet cutoff move becomes a killer at its ply, the refuted quiet loses history
+        let mut moves = vec![(3, 11), (4, 5), (18, 33)];
+        move_orderer.order_moves(&chessboard, &mut moves, PlayerColor::White, None, 2, None);
+        assert_eq!(moves, vec![(18, 33), (4, 5), (3, 11)]);
+
+        // and the countermove to the previous move on other plies
+        let mut moves = vec![(3, 11), (4, 5), (18, 33)];
+        move_orderer.order_moves(&chessboard, &mut moves, PlayerColor::White, None, 5, Some((60, 52)));
+        assert_eq!(moves[0], (18, 33));
+
+        move_orderer.record_cutoff(&chessboard, PlayerColor::White, (3, 59), 0, &[], 4, 2, None);
+        assert_eq!(move_orderer.first_move_cutoff_rate(), 50.0);
+
+        move_orderer.new_search();
+        assert_eq!(move_orderer.cutoffs(), 0);
+    }
+
+    #[test]
+    fn test_quiescence_search() {
+        println!("\n*******************\nRunning test_quiescence_search()\n*******************\n");
+
+        let move_orderer = MoveOrderer::new();
+        let options = SearchOptions::default();
+        assert!(generate_capture_moves(&Chessboard::new(), PlayerColor::White, false).is_empty());
+
+        // the d5 pawn hangs: the capture is resolved before evaluating
+        let chessboard = Chessboard::from_fen("4k3/8/8/3p4/8/8/8/3QK3 w - - 0 1").unwrap();
+        assert_eq!(generate_capture_moves(&chessboard, PlayerColor::White, false), vec![(3, 35)]);
+        let stand_pat = evaluate(&chessboard, PlayerColor::White);
+        let value = quiescence_search(&chessboard, -Score::INFINITY, Score::INFINITY, PlayerColor::White, 0, 0, &options, &move_orderer, &mut SearchStats::default());
+        assert_eq!(value, Score::centipawns(stand_pat + PAWN_VALUE));
+
+        // defended by the c6 pawn: taking it loses the queen, so white stands pat
+        let chessboard = Chessboard::from_fen("4k3/8/2p5/3p4/8/8/8/3QK3 w - - 0 1").unwrap();
+        let stand_pat = evaluate(&chessboard, PlayerColor::White);
+        let value = quiescence_search(&chessboard, -Score::INFINITY, Score::INFINITY, PlayerColor::White, 0, 0, &options, &move_orderer, &mut SearchStats::default());
+        assert_eq!(value, Score::centipawns(stand_pat));
+
+        // promotions are searched even without a capture
+        let chessboard = Chessboard::from_fen("7k/2P5/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        assert_eq!(generate_capture_moves(&chessboard, PlayerColor::White, false), vec![(50, 58)]);
+        let stand_pat = evaluate(&chessboard, PlayerColor::White);
+        let value = quiescence_search(&chessboard, -Score::INFINITY, Score::INFINITY, PlayerColor::White, 0, 0, &options, &move_orderer, &mut SearchStats::default());
+        assert_eq!(value, Score::centipawns(stand_pat + QUEEN_VALUE - PAWN_VALUE));
+    }
+
+    #[test]
+    fn test_selective_search() {
+        println!("\n*******************\nRunning test_selective_search()\n*******************\n");
+
+        let mut options = SearchOptions::default();
+        assert_eq!(options.set_option("NullMove", "false"), Ok(()));
+        assert_eq!(options.set_option("LmrDivisor", "3.5"), Ok(()));
+        assert!(!options.null_move_pruning);
+        assert_eq!(options.lmr_divisor, 3.5);
+        assert_eq!(options.set_option("Razoring", "maybe"), Err(OptionError::InvalidValue));
+        assert_eq!(options.set_option("LmrBase", "-1"), Err(OptionError::InvalidValue));
+        assert_eq!(options.set_option("UCI_Chess960", "true"), Err(OptionError::UnknownOption));
+
+        // reductions grow with depth and move number but never reach the quiescence search
+        let reductions = ReductionTable::new(0.75, 2.25);
+        assert_eq!(reductions.reduction(3, 1), 0);
+        assert!(reductions.reduction(10, 30) >= reductions.reduction(6, 10));
+        assert!(reductions.reduction(6, 10) > 0);
+        for depth in 1..20 {
+            assert!(reductions.reduction(depth, 63) <= depth.saturating_sub(2));
+        }
+
+        // no null moves with only pawns left
+        let chessboard = Chessboard::from_fen("4k3/4p3/8/8/8/8/3P4/3NK3 w - e3 0 1").unwrap();
+        assert!(has_non_pawn_material(&chessboard, PlayerColor::White));
+        assert!(!has_non_pawn_material(&chessboard, PlayerColor::Black));
+        let mut null_state = chessboard.clone();
+        null_state.make_null_move();
+        assert_eq!(null_state.side_to_move, PlayerColor::Black);
+        assert_eq!(null_state.en_passant, None);
+        assert_ne!(null_state.zobrist_hash(), chessboard.zobrist_hash());
+
+        // the selective search finds the same mate as the full-width one
+        let chessboard = Chessboard::from_fen("k7/8/2K5/8/8/8/8/7R w - - 0 1").unwrap();
+        let selective = search(
+            &chessboard,
+            &SearchLimits::default(),
+            &SearchOptions::default(),
+            &TranspositionTable::new(1),
+            &MoveOrderer::new(),
+            &StopFlag::new(),
+            None,
+        );
+        let full_width = search(
+            &chessboard,
+            &SearchLimits::default(),
+            &SearchOptions::full_width(),
+            &TranspositionTable::new(1),
+            &MoveOrderer::new(),
+            &StopFlag::new(),
+            None,
+        );
+        assert_eq!(selective.score, Score::mate_in(3));
+        assert_eq!(full_width.score, selective.score);
+    }
+
+    #[test]
+    fn test_search_extensions() {
+        println!("\n*******************\nRunning test_search_extensions()\n*******************\n");
+
+        let mut options = SearchOptions::default();
+        assert_eq!(options.set_option("SingularExtensions", "off"), Ok(()));
+        assert_eq!(options.set_option("CheckExtensions", "on"), Ok(()));
+        assert!(!options.singular_extensions);
+        assert!(options.check_extensions);
+
+        // pushes to the seventh rank, for either side, and nothing else
+        let chessboard = Chessboard::from_fen("4k3/8/1P6/2P5/8/5p2/8/4K3 w - - 0 1").unwrap();
+        assert!(is_passed_pawn_push(&chessboard, (41, 49), PlayerColor::White));
+        assert!(!is_passed_pawn_push(&chessboard, (34, 42), PlayerColor::White));
+        assert!(is_passed_pawn_push(&chessboard, (21, 13), PlayerColor::Black));
+        assert!(!is_passed_pawn_push(&chessboard, (4, 12), PlayerColor::White));
+
+        // the same mate with and without extensions
+        let chessboard = Chessboard::from_fen("k7/8/2K5/8/8/8/8/7R w - - 0 1").unwrap();
+        let without_extensions = SearchOptions {
+            check_extensions: false,
+            singular_extensions: false,
+            passed_pawn_extensions: false,
+            ..SearchOptions::default()
+        };
+        for options in [SearchOptions::default(), without_extensions] {
+            let result = search(
+                &chessboard,
+                &SearchLimits::default(),
+                &options,
+                &TranspositionTable::new(1),
+                &MoveOrderer::new(),
+                &StopFlag::new(),
+                None,
+            );
+            assert_eq!(result.score, Score::mate_in(3));
+        }
+    }
+
+    #[test]
+    fn test_score() {
+        println!("\n*******************\nRunning test_score()\n*******************\n");
+
+        // faster mates score higher, slower losses score higher too
+        assert!(Score::mate_in(1) > Score::mate_in(3));
+        assert!(Score::mated_in(4) > Score::mated_in(2));
+        assert!(Score::mate_in(99) > Score::centipawns(i64::MAX));
+        assert!(Score::mated_in(99) < Score::centipawns(i64::MIN));
+        assert_eq!(-Score::mate_in(5), Score::mated_in(5));
+        assert_eq!(Score::centipawns(MATE_BOUND).value(), MATE_BOUND - 1);
+        assert!(!Score::centipawns(MATE_BOUND).is_mate());
+        assert!(!Score::INFINITY.is_mate());
+
+        assert_eq!(Score::mate_in(1).mate_moves(), Some(1));
+        assert_eq!(Score::mate_in(5).mate_moves(), Some(3));
+        assert_eq!(Score::mated_in(4).mate_moves(), Some(-2));
+        assert_eq!(Score::centipawns(35).mate_moves(), None);
+        assert_eq!(Score::mate_in(3).to_string(), "mate 2");
+        assert_eq!(Score::mated_in(2).to_string(), "mate -1");
+        assert_eq!(Score::centipawns(-35).to_string(), "cp -35");
+
+        // relative to the node in the table, relative to the root outside it
+        assert_eq!(Score::mate_in(7).to_node_relative(4), Score::mate_in(3));
+        assert_eq!(Score::mate_in(3).to_root_relative(4), Score::mate_in(7));
+        assert_eq!(Score::mated_in(7).to_node_relative(4).to_root_relative(2), Score::mated_in(5));
+        assert_eq!(Score::centipawns(50).to_node_relative(9), Score::centipawns(50));
+
+        // the mate is reported in moves
+        let chessboard = Chessboard::from_fen("6k1/5ppp/8/8/8/8/8/R3K3 w - - 0 1").unwrap();
+        let result = search(
+            &chessboard,
+            &SearchLimits::move_time(Duration::from_millis(200)),
+            &SearchOptions::default(),
+            &TranspositionTable::new(1),
+            &MoveOrderer::new(),
+            &StopFlag::new(),
+            None,
+        );
+        assert_eq!(result.score.to_string(), "mate 1");
+    }
+
+    #[test]
+    fn test_lazy_smp() {
+        println!("\n*******************\nRunning test_lazy_smp()\n*******************\n");
+
+        let mut options = SearchOptions::default();
+        assert_eq!(options.set_option("Threads", "4"), Ok(()));
+        assert_eq!(options.threads, 4);
+        assert_eq!(options.set_option("Threads", "0"), Err(OptionError::InvalidValue));
+
+        // helpers share the table but the main thread's answer is the one returned
+        let chessboard = Chessboard::from_fen("k7/8/2K5/8/8/8/8/7R w - - 0 1").unwrap();
+        let result = search(
+            &chessboard,
+            &SearchLimits::default(),
+            &options,
+            &TranspositionTable::new(1),
+            &MoveOrderer::new(),
+            &StopFlag::new(),
+            None,
+        );
+        assert_eq!(result.score, Score::mate_in(3));
+
+        let results = time_to_depth(2, 2);
+        assert_eq!(results.iter().map(|result| result.threads).collect::<Vec<usize>>(), vec![1, 2]);
+        assert_eq!(results[0].speedup(&results[0]), 1.0);
+    }
+
+    #[test]
+    fn test_search_limits() {
+        println!("\n*******************\nRunning test_search_limits()\n*******************\n");
+
+        let limits = SearchLimits::clock(
+            Duration::from_secs(10),
+            Duration::from_secs(20),
+            Duration::from_secs(1),
+            Duration::from_secs(2),
+            Some(5),
+        );
+        let time_control = limits.time_control(PlayerColor::Black);
+        assert_eq!(time_control.remaining, Some(Duration::from_secs(20)));
+        assert_eq!(time_control.increment, Duration::from_secs(2));
+        assert_eq!(time_control.moves_to_go, Some(5));
+        assert_eq!(SearchLimits::infinite().time_control(PlayerColor::White).remaining, None);
+        assert!(!SearchLimits::default().is_limited());
+
+        let options = SearchOptions::default();
+        let chessboard = Chessboard::from_fen("r1bqk1nr/pppp1ppp/2n5/2b1p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 4 4").unwrap();
+        let result = search(
+            &chessboard,
+            &SearchLimits::depth(3),
+            &options,
+            &TranspositionTable::new(1),
+            &MoveOrderer::new(),
+            &StopFlag::new(),
+            None,
+        );
+        assert_eq!(result.depth, 3);
+
+        // the node limit gives the same answer every time
+        let first = search(
+            &chessboard,
+            &SearchLimits::nodes(3000),
+            &options,
+            &TranspositionTable::new(1),
+            &MoveOrderer::new(),
+            &StopFlag::new(),
+            None,
+        );
+        let second = search(
+            &chessboard,
+            &SearchLimits::nodes(3000),
+            &options,
+            &TranspositionTable::new(1),
+            &MoveOrderer::new(),
+            &StopFlag::new(),
+            None,
+        );
+        assert_eq!((first.depth, &first.lines), (second.depth, &second.lines));
+        assert_eq!(first.stats.total_nodes(), second.stats.total_nodes());
+
+        // the threads share the limit instead of each searching that many nodes
+        let threaded = SearchOptions {
+            threads: 4,
+            ..SearchOptions::default()
+        };
+        let result = search(
+            &chessboard,
+            &SearchLimits::nodes(20_000),
+            &threaded,
+            &TranspositionTable::new(1),
+            &MoveOrderer::new(),
+            &StopFlag::new(),
+            None,
+        );
+        assert!(result.stats.total_nodes() >= 20_000);
+        assert!(result.stats.total_nodes() < 40_000);
+
+        // mate in two is enough, no need to look further
+        let chessboard = Chessboard::from_fen("k7/8/2K5/8/8/8/8/7R w - - 0 1").unwrap();
+        let result = search(
+            &chessboard,
+            &SearchLimits::mate(2),
+            &options,
+            &TranspositionTable::new(1),
+            &MoveOrderer::new(),
+            &StopFlag::new(),
+            None,
+        );
+        assert_eq!(result.score.mate_moves(), Some(2));
+
+        // an infinite search only ends when stopped, with the best move found so far
+        let chessboard = Chessboard::from_fen("4k3/8/8/3p4/8/8/8/3QK3 w - - 0 1").unwrap();
+        let stop_flag = StopFlag::new();
+        let transposition_table = TranspositionTable::new(1);
+        let move_orderer = MoveOrderer::new();
+        let start = Instant::now();
+        let result = std::thread::scope(|scope| {
+            let search_thread = scope.spawn(|| {
+                search(
+                    &chessboard,
+                    &SearchLimits::infinite(),
+                    &options,
+                    &transposition_table,
+                    &move_orderer,
+                    &stop_flag,
+                    None,
+                )
+            });
+            std::thread::sleep(Duration::from_millis(300));
+            stop_flag.stop();
+            search_thread.join().unwrap()
+        });
+        assert!(start.elapsed() < Duration::from_secs(2));
+        assert!(stop_flag.is_stopped());
+        assert!(result.depth >= 1);
+        assert_eq!(result.best_move, (3, 35));
+        stop_flag.reset();
+        assert!(!stop_flag.is_stopped());
+    }
+
+    #[test]
+    fn test_search_info() {
+        println!("\n*******************\nRunning test_search_info()\n*******************\n");
+
+        // one report per completed iteration, the last one matching the result
+        let chessboard = Chessboard::from_fen(STARTING_FEN).unwrap();
+        let infos = std::sync::Mutex::new(vec![]);
+        let result = search(
+            &chessboard,
+            &SearchLimits::depth(4),
+            &SearchOptions::default(),
+            &TranspositionTable::new(1),
+            &MoveOrderer::new(),
+            &StopFlag::new(),
+            Some(&|info: &SearchInfo| infos.lock().unwrap().push(info.clone())),
+        );
+        let infos = infos.into_inner().unwrap();
+        assert_eq!(infos.iter().map(|info| info.depth).collect::<Vec<usize>>(), vec![1, 2, 3, 4]);
+        assert!(infos.windows(2).all(|pair| pair[0].nodes < pair[1].nodes));
+        assert!(infos.iter().all(|info| info.seldepth >= info.depth && info.hashfull <= 1000));
+
+        let last = infos.last().unwrap();
+        assert_eq!(last.score, result.score);
+        assert_eq!(last.principal_variation, result.principal_variation);
+
+        // the only legal move is reported once, with its line
+        let chessboard = Chessboard::from_fen("k7/8/8/8/8/8/6r1/7K w - - 0 1").unwrap();
+        let infos = std::sync::Mutex::new(vec![]);
+        let result = search(
+            &chessboard,
+            &SearchLimits::depth(4),
+            &SearchOptions::default(),
+            &TranspositionTable::new(1),
+            &MoveOrderer::new(),
+            &StopFlag::new(),
+            Some(&|info: &SearchInfo| infos.lock().unwrap().push(info.clone())),
+        );
+        let infos = infos.into_inner().unwrap();
+        assert_eq!((result.best_move, result.depth), ((7, 14), 1));
+        assert_eq!(result.principal_variation.first(), Some(&(7, 14)));
+        assert_eq!(result.lines.len(), 1);
+        assert_eq!(infos.len(), 1);
+        assert_eq!((infos[0].score, &infos[0].principal_variation), (result.score, &result.principal_variation));
+
+        let info = SearchInfo {
+            depth: 2,
+            seldepth: 3,
+            multipv: 1,
+            score: Score::centipawns(35),
+            nodes: 500,
+            nps: SearchInfo::nodes_per_second(500, Duration::from_millis(250)),
+            time: Duration::from_millis(250),
+            hashfull: 1,
+            principal_variation: vec![(12, 28), (52, 36)],
+        };
+        assert_eq!(info.nps, 2000);
+        assert_eq!(
+            info.to_string(),
+            "depth 2 seldepth 3 multipv 1 score cp 35 nodes 500 nps 2000 time 250 hashfull 1 pv e2e4 e7e5"
+        );
+    }
+
+    #[test]
+    fn test_multi_pv() {
+        println!("\n*******************\nRunning test_multi_pv()\n*******************\n");
+
+        // the back rank mate first, then the best of the other moves, each with its own root move
+        let chessboard = Chessboard::from_fen("6k1/5ppp/8/8/8/8/8/R3K3 w - - 0 1").unwrap();
+        let options = SearchOptions {
+            multi_pv: 3,
+            ..SearchOptions::default()
+        };
+        let infos = std::sync::Mutex::new(vec![]);
+        let result = search(
+            &chessboard,
+            &SearchLimits::depth(4),
+            &options,
+            &TranspositionTable::new(1),
+            &MoveOrderer::new(),
+            &StopFlag::new(),
+            Some(&|info: &SearchInfo| infos.lock().unwrap().push((info.depth, info.multipv))),
+        );
+        assert_eq!(result.lines.len(), 3);
+        assert_eq!(result.best_move, (0, 56));
+        assert_eq!(result.score.mate_moves(), Some(1));
+        assert_eq!(
+            result.lines[0],
+            SearchLine { score: result.score, principal_variation: result.principal_variation.clone() }
+        );
+        assert!(result.lines[1..].iter().all(|line| !line.score.is_mate()));
+        assert!(result.lines.windows(2).all(|pair| pair[0].score >= pair[1].score));
+        let mut root_moves: Vec<(u64, u64)> = result.lines.iter().map(|line| line.principal_variation[0]).collect();
+        root_moves.dedup();
+        assert_eq!(root_moves.len(), 3);
+        assert_eq!(infos.into_inner().unwrap()[..3], [(1, 1), (1, 2), (1, 3)]);
+
+        // never more lines than root moves
+        let chessboard = Chessboard::from_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        let options = SearchOptions {
+            multi_pv: 10,
+            ..SearchOptions::default()
+        };
+        let result = search(
+            &chessboard,
+            &SearchLimits::depth(3),
+            &options,
+            &TranspositionTable::new(1),
+            &MoveOrderer::new(),
+            &StopFlag::new(),
+            None,
+        );
+        assert_eq!(result.lines.len(), 5);
+
+        let mut options = SearchOptions::default();
+        assert_eq!(options.set_option("MultiPV", "4"), Ok(()));
+        assert_eq!(options.multi_pv, 4);
+        assert_eq!(options.set_option("MultiPV", "0"), Err(OptionError::InvalidValue));
+    }
+
+    #[test]
+    fn test_search_side_to_move() {
+        println!("\n*******************\nRunning test_search_side_to_move()\n*******************\n");
+
+        // the back rank mate, mirrored for black
+        let chessboard = Chessboard::from_fen("r5k1/8/8/8/8/8/5PPP/6K1 b - - 0 1").unwrap();
+        let result = search(
+            &chessboard,
+            &SearchLimits::depth(3),
+            &SearchOptions::default(),
+            &TranspositionTable::new(1),
+            &MoveOrderer::new(),
+            &StopFlag::new(),
+            None,
+        );
+        assert_eq!(result.best_move, (56, 0));
+        assert_eq!(result.score.mate_moves(), Some(1));
+
+        // a position and its mirror image score the same for the side to move, apart from the
+        // evaluation's bonus for white counting for one side and against the other
+        let white = Chessboard::from_fen("r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3").unwrap();
+        let black = Chessboard::from_fen("rnbqkb1r/pppp1ppp/5n2/4p3/4P3/2N5/PPPP1PPP/R1BQKBNR b KQkq - 2 3").unwrap();
+        let search_at_depth = |chessboard: &Chessboard| {
+            search(
+                chessboard,
+                &SearchLimits::depth(3),
+                &SearchOptions::default(),
+                &TranspositionTable::new(1),
+                &MoveOrderer::new(),
+                &StopFlag::new(),
+                None,
+            )
+        };
+        let white_result = search_at_depth(&white);
+        let black_result = search_at_depth(&black);
+        assert_eq!(white_result.score.value() - black_result.score.value(), 20);
+
+        // self-play: every move the search picks is legal for the side it was picked for
+        let mut chessboard = Chessboard::from_fen(STARTING_FEN).unwrap();
+        for _ in 0..6 {
+            let player_color = chessboard.side_to_move;
+            let best_move = search_at_depth(&chessboard).best_move;
+            let from = Chessboard::convert_index_to_square(best_move.0);
+            let to = Chessboard::convert_index_to_square(best_move.1);
+            assert_eq!(chessboard.perform_move(&from, &to, player_color), Ok(()));
+            assert_eq!(chessboard.side_to_move, player_color.opposite());
+        }
+    }
+
+    #[test]
+    fn test_search_stats() {
+        println!("\n*******************\nRunning test_search_stats()\n*******************\n");
+
+        let chessboard = Chessboard::from_fen("r2q1rk1/ppp2ppp/2np1n2/2b1p3/2B1P1b1/2NP1N2/PPP2PPP/R1BQ1RK1 w - - 2 8").unwrap();
+        let search_with = |options: &SearchOptions| {
+            search(
+                &chessboard,
+                &SearchLimits::depth(5),
+                options,
+                &TranspositionTable::new(1),
+                &MoveOrderer::new(),
+                &StopFlag::new(),
+                None,
+            )
+        };
+
+        let stats = search_with(&SearchOptions::default()).stats;
+        assert!(stats.nodes > 0 && stats.qnodes > 0);
+        assert!(stats.first_move_cutoffs <= stats.beta_cutoffs && stats.beta_cutoffs > 0);
+        assert!(stats.tt_hits <= stats.tt_probes && stats.tt_hits > 0);
+        assert!(stats.null_move_cutoffs > 0);
+        assert!(stats.first_move_cutoff_rate() > 50.0);
+        assert_eq!(stats.iterations.iter().map(|iteration| iteration.depth).collect::<Vec<usize>>(), vec![1, 2, 3, 4, 5]);
+        assert_eq!(stats.iterations.iter().map(|iteration| iteration.nodes).sum::<u64>(), stats.total_nodes());
+        assert_eq!(stats.iterations[0].branching_factor, None);
+        assert!(stats.iterations[1..].iter().all(|iteration| iteration.branching_factor.is_some()));
+
+        // nothing pruned or reduced in a full-width search
+        let stats = search_with(&SearchOptions::full_width()).stats;
+        assert_eq!((stats.null_move_cutoffs, stats.lmr_researches), (0, 0));
+
+        // helpers add their counters, not their iterations
+        let mut stats = SearchStats {
+            qnodes: 10,
+            ..SearchStats::default()
+        };
+        stats.end_iteration(1, 0, Duration::from_millis(1));
+        stats.nodes = 20;
+        stats.qnodes = 40;
+        stats.end_iteration(2, 10, Duration::from_millis(2));
+        assert_eq!(stats.iterations[1].nodes, 50);
+        assert_eq!(stats.iterations[1].branching_factor, Some(5.0));
+
+        let helper = SearchStats {
+            nodes: 5,
+            beta_cutoffs: 4,
+            first_move_cutoffs: 3,
+            ..stats.clone()
+        };
+        stats.merge(&helper);
+        assert_eq!((stats.nodes, stats.qnodes, stats.beta_cutoffs, stats.first_move_cutoffs), (25, 80, 4, 3));
+        assert_eq!(stats.first_move_cutoff_rate(), 75.0);
+        assert_eq!(stats.iterations.len(), 2);
+    }
+
+    #[test]
+    fn test_deterministic_search() {
+        println!("\n*******************\nRunning test_deterministic_search()\n*******************\n");
+
+        // more threads and a clock are both ignored, only the node limit ends the search
+        let chessboard = Chessboard::from_fen("r1bqk1nr/pppp1ppp/2n5/2b1p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 4 4").unwrap();
+        let options = SearchOptions {
+            threads: 4,
+            ..SearchOptions::deterministic()
+        };
+        assert_eq!(options.search_threads(), 1);
+        let limits = SearchLimits {
+            nodes: Some(20_000),
+            move_time: Some(Duration::from_millis(1)),
+            ..SearchLimits::default()
+        };
+        let search_once = || {
+            search(&chessboard, &limits, &options, &TranspositionTable::new(1), &MoveOrderer::new(), &StopFlag::new(), None)
+        };
+        let first = search_once();
+        let second = search_once();
+        // quiescence nodes count towards the limit too
+        assert!(first.stats.nodes < 20_000);
+        assert!(first.stats.total_nodes() >= 20_000);
+        assert_eq!((first.depth, &first.lines), (second.depth, &second.lines));
+        assert_eq!(first.stats.total_nodes(), second.stats.total_nodes());
+
+        let mut options = SearchOptions::default();
+        assert_eq!(options.set_option("Deterministic", "on"), Ok(()));
+        assert!(options.deterministic);
+
+        // the bench signature: update it only for changes meant to change the search
+        assert_eq!(signature(SIGNATURE_DEPTH), 10748);
+    }
+
+    #[test]
+    fn test_pondering() {
+        println!("\n*******************\nRunning test_pondering()\n*******************\n");
+
+        let chessboard = Chessboard::from_fen(STARTING_FEN).unwrap();
+        let limits = SearchLimits::move_time(Duration::from_millis(100));
+        let transposition_table = TranspositionTable::new(1);
+        let move_orderer = MoveOrderer::new();
+        let search_pondering = |stop_flag: &StopFlag| {
+            search(&chessboard, &limits, &SearchOptions::default(), &transposition_table, &move_orderer, stop_flag, None)
+        };
+
+        // the clock only starts on the ponder hit, then the search ends on its own
+        let stop_flag = StopFlag::pondering();
+        assert!(stop_flag.is_pondering());
+        std::thread::scope(|scope| {
+            let ponder_thread = scope.spawn(|| search_pondering(&stop_flag));
+            std::thread::sleep(Duration::from_millis(400));
+            assert!(!ponder_thread.is_finished());
+
+            stop_flag.ponder_hit();
+            let hit = Instant::now();
+            let result = ponder_thread.join().unwrap();
+            assert!(hit.elapsed() < Duration::from_millis(1000));
+            assert!(result.depth > 0);
+            assert!(!stop_flag.is_stopped());
+        });
+
+        // on a miss the search is stopped, its table entries stay for the next one
+        let stop_flag = StopFlag::pondering();
+        std::thread::scope(|scope| {
+            let ponder_thread = scope.spawn(|| search_pondering(&stop_flag));
+            std::thread::sleep(Duration::from_millis(200));
+            stop_flag.stop();
+            ponder_thread.join().unwrap();
+        });
+        assert!(transposition_table.hashfull() > 0);
+
+        stop_flag.reset();
+        assert!(!stop_flag.is_stopped() && !stop_flag.is_pondering());
+    }
+
+    #[test]
+    fn test_see() {
+        println!("\n*******************\nRunning test_see()\n*******************\n");
+
+        let positions = [
+            // an undefended pawn
+            ("1k1r4/1pp4p/p7/4p3/8/P5P1/1PP4P/2K1R3 w - - 0 1", (4, 36), PAWN_VALUE),
+            // knight, rook and queen behind it against knight, bishop and the queen behind it
+            ("1k1r3q/1ppn3p/p4b2/4p3/8/P2N2P1/1PP1R1BP/2K1Q3 w - - 0 1", (19, 36), PAWN_VALUE - KNIGHT_VALUE),
+            // the second rook only joins in once the first is gone
+            ("3rk3/8/8/3r4/8/3R4/3R4/4K3 w - - 0 1", (19, 35), ROOK_VALUE),
+            // the queen is lost for a pawn
+            ("3r2k1/8/8/3p4/8/8/8/3QK3 w - - 0 1", (3, 35), PAWN_VALUE - QUEEN_VALUE),
+            // the pawn recaptures, not the rook
+            ("3rk3/8/2p5/3n4/8/8/8/3RK3 w - - 0 1", (3, 35), KNIGHT_VALUE - ROOK_VALUE),
+            // en passant, won outright and then traded
+            ("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1", (36, 43), PAWN_VALUE),
+            ("3rk3/8/8/3pP3/8/8/8/4K3 w - d6 0 1", (36, 43), 0),
+            // the king recaptures, unless the square is still attacked
+            ("4k3/3r4/8/8/8/8/3P4/4K3 b - - 0 1", (51, 11), PAWN_VALUE - ROOK_VALUE),
+            ("3rk3/3r4/8/8/8/8/3P4/4K3 b - - 0 1", (51, 11), PAWN_VALUE),
+            // the queen is lost, the rook behind it takes a pawn back
+            ("4k3/8/4p3/3p4/8/8/3Q4/3RK3 w - - 0 1", (11, 35), 2 * PAWN_VALUE - QUEEN_VALUE),
+            // the defender doesn't recapture when the attacker behind would win its rook
+            ("3rk3/8/8/3n4/8/8/3R4/3RK3 w - - 0 1", (11, 35), KNIGHT_VALUE),
+        ];
+
+        for (fen, next_move, expected) in positions {
+            let chessboard = Chessboard::from_fen(fen).unwrap();
+            assert_eq!(chessboard.see(next_move), expected, "{}", fen);
+            assert!(chessboard.see_at_least(next_move, expected));
+            assert!(!chessboard.see_at_least(next_move, expected + 1));
+        }
+    }
+
+    #[cfg(feature = "syzygy")]
+    #[test]
+    fn test_syzygy() {
+        println!("\n*******************\nRunning test_syzygy()\n*******************\n");
+
+        // KQvK, KRvK and KPvK, the directory above holds no tables of its own
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/test-data/syzygy");
+        assert_eq!(Tablebase::open("no/such/directory").unwrap_err(), TablebaseError::DirectoryNotFound);
+        assert_eq!(
+            Tablebase::open(concat!(env!("CARGO_MANIFEST_DIR"), "/test-data")).unwrap_err(),
+            TablebaseError::NoTables
+        );
+
+        let mut options = SearchOptions::deterministic();
+        assert_eq!(options.set_option("SyzygyPath", "no/such/directory"), Err(OptionError::InvalidValue));
+        assert_eq!(options.tablebase, None);
+        options.set_option("SyzygyPath", path).unwrap();
+        let tablebase = options.tablebase.clone().unwrap();
+        assert_eq!(tablebase.max_pieces(), 3);
+
+        // the rook wins with either side to move, not right after a quiet move as far as WDL goes
+        let white = Chessboard::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 0 1").unwrap();
+        let black = Chessboard::from_fen("4k3/8/8/8/8/8/8/R3K3 b - - 0 1").unwrap();
+        assert!(tablebase.probe_wdl(&white, 0).unwrap() > Score::centipawns(QUEEN_VALUE));
+        assert!(tablebase.probe_wdl(&black, 0).unwrap() < Score::centipawns(-QUEEN_VALUE));
+        assert_eq!(tablebase.probe_wdl(&Chessboard::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 1 1").unwrap(), 0), None);
+        // stalemate with the pawn in front of the king
+        assert_eq!(tablebase.probe_wdl(&Chessboard::from_fen("4k3/4P3/4K3/8/8/8/8/8 b - - 0 1").unwrap(), 0), Some(Score::DRAW));
+        // too many pieces for the tables
+        assert_eq!(tablebase.probe_wdl(&Chessboard::new(), 0), None);
+
+        let search_with = |chessboard: &Chessboard, options: &SearchOptions| search(
+            chessboard,
+            &SearchLimits::depth(4),
+            options,
+            &TranspositionTable::new(1),
+            &MoveOrderer::new(),
+            &StopFlag::new(),
+            None
+        );
+
+        // the root move comes straight from the tables, without searching, and keeps the
+        // win: the king steps up to guard the rook the black king attacks, or the rook leaves
+        let attacked = Chessboard::from_fen("8/8/8/8/8/2R5/1k6/4K3 w - - 0 1").unwrap();
+        let result = search_with(&attacked, &options);
+        assert_eq!((result.depth, result.stats.tb_hits), (0, 1));
+        assert_eq!(result.principal_variation, vec![result.best_move]);
+        assert!(result.score > Score::centipawns(QUEEN_VALUE));
+        let mut after = attacked.clone();
+        let from = Chessboard::convert_index_to_square(result.best_move.0);
+        let to = Chessboard::convert_index_to_square(result.best_move.1);
+        after.perform_move(&from, &to, PlayerColor::White).unwrap();
+        assert!(tablebase.probe_root(&after).unwrap().1 < Score::centipawns(-QUEEN_VALUE));
+
+        // too many pieces at the root, the tables score the position after the capture
+        let capture = Chessboard::from_fen("4k3/8/8/8/8/3p4/8/3QK3 w - - 0 1").unwrap();
+        let result = search_with(&capture, &options);
+        assert_eq!(result.best_move, (3, 19));
+        assert!(result.stats.tb_hits > 0);
+        assert!(result.score > Score::centipawns(QUEEN_VALUE));
+
+        options.set_option("SyzygyPath", "").unwrap();
+        assert_eq!(options.tablebase, None);
+    }
+
+    #[test]
+    fn test_polyglot_hash() {
+        println!("\n*******************\nRunning test_polyglot_hash()\n*******************\n");
+
+        // the reference keys of the Polyglot book format
+        let positions = [
+            ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", 0x463B_9618_1691_FC9C),
+            ("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1", 0x823C_9B50_FD11_4196),
+            ("rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 2", 0x0756_B944_61C5_0FB0),
+            ("rnbqkbnr/ppp1pppp/8/3pP3/8/8/PPPP1PPP/RNBQKBNR b KQkq - 0 2", 0x662F_AFB9_65DB_29D4),
+            ("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3", 0x22A4_8B5A_8E47_FF78),
+            ("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPPKPPP/RNBQ1BNR b kq - 0 3", 0x652A_607C_A3F2_42C1),
+            ("rnbq1bnr/ppp1pkpp/8/3pPp2/8/8/PPPPKPPP/RNBQ1BNR w - - 0 4", 0x00FD_D303_C946_BDD9),
+            ("rnbqkbnr/p1pppppp/8/8/PpP4P/8/1P1PPPP1/RNBQKBNR b KQkq c3 0 3", 0x3C81_23EA_7B06_7637),
+            ("rnbqkbnr/p1pppppp/8/8/P6P/R1p5/1P1PPPP1/1NBQKBNR b Kkq - 0 4", 0x5C3F_9B82_9B27_9560),
+        ];
+
+        for (fen, expected) in positions {
+            assert_eq!(Chessboard::from_fen(fen).unwrap().polyglot_hash(), expected, "{}", fen);
+        }
+
+        // the same position reached by moves
+        let mut chessboard = Chessboard::new();
+        chessboard.perform_move("e2", "e4", PlayerColor::White).unwrap();
+        assert_eq!(chessboard.polyglot_hash(), 0x823C_9B50_FD11_4196);
+    }
+
+    #[test]
+    fn test_opening_book() {
+        println!("\n*******************\nRunning test_opening_book()\n*******************\n");
+
+        // castling is written as the king taking its rook, promotions name their piece
+        let castling = Chessboard::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+        assert_eq!(encode_move(&castling, (4, 6)), (4 << 6) | 7);
+        assert_eq!(encode_move(&castling, (4, 2)), 4 << 6);
+        assert_eq!(decode_move(&castling, (4 << 6) | 7), Some((4, 6)));
+        assert_eq!(decode_move(&castling, 4 << 6), Some((4, 2)));
+        let promotion = Chessboard::from_fen("4k3/P7/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        assert_eq!(encode_move(&promotion, (48, 56)), (4 << 12) | (48 << 6) | 56);
+        assert_eq!(decode_move(&promotion, (1 << 12) | (48 << 6) | 56), None);
+
+        let chessboard = Chessboard::new();
+        let key = chessboard.polyglot_hash();
+        let entry = |next_move, weight| BookEntry {
+            key,
+            raw_move: encode_move(&chessboard, next_move),
+            weight,
+            learn: 0,
+        };
+        let book = OpeningBook::new(vec![
+            BookEntry { key: key + 1, ..entry((12, 28), 100) },
+            entry((11, 27), 5),
+            entry((12, 28), 10),
+            entry((6, 21), 0),
+            BookEntry { key: key - 1, ..entry((12, 28), 100) },
+        ]);
+        assert_eq!(book.moves(&chessboard), vec![((11, 27), 5), ((12, 28), 10), ((6, 21), 0)]);
+        assert_eq!(book.choose_move(&chessboard, BookSelection::Best, 0), Some((12, 28)));
+
+        // by weight, never the move weighted zero
+        let picks: Vec<_> = (0..15).map(|random| book.choose_move(&chessboard, BookSelection::Weighted, random).unwrap()).collect();
+        assert_eq!(picks.iter().filter(|next_move| **next_move == (11, 27)).count(), 5);
+        assert_eq!(picks.iter().filter(|next_move| **next_move == (12, 28)).count(), 10);
+
+        // out of the book
+        let mut after_e4 = chessboard.clone();
+        after_e4.perform_move("e2", "e4", PlayerColor::White).unwrap();
+        assert_eq!(book.choose_move(&after_e4, BookSelection::Best, 0), None);
+
+        let bytes = book.to_bytes();
+        assert_eq!(bytes.len(), 5 * POLYGLOT_ENTRY_SIZE);
+        assert_eq!(OpeningBook::from_bytes(&bytes).unwrap(), book);
+        assert_eq!(OpeningBook::from_bytes(&bytes[1..]).unwrap_err(), BookError::InvalidSize);
+        assert_eq!(OpeningBook::open("no/such/book.bin").unwrap_err(), BookError::FileNotFound);
+
+        // get_best_move plays from the book without searching
+        let path = std::env::temp_dir().join("engine_test_book.bin");
+        std::fs::write(&path, &bytes).unwrap();
+        let mut options = SearchOptions::deterministic();
+        assert_eq!(options.set_option("BookFile", "no/such/book.bin"), Err(OptionError::InvalidValue));
+        options.set_option("BookFile", path.to_str().unwrap()).unwrap();
+        options.set_option("BookSelection", "best").unwrap();
+        let best_move = get_best_move(
+            &chessboard,
+            &SearchLimits::nodes(1),
+            &options,
+            &TranspositionTable::new(1),
+            &MoveOrderer::new(),
+            &StopFlag::new(),
+            None
+        );
+        assert_eq!(best_move, (12, 28));
+    }
+
+    #[test]
+    fn test_pgn() {
+        println!("\n*******************\nRunning test_pgn()\n*******************\n");
+
+        let contents = r#"
+[Event "Casual \"blitz\""]
+[WhiteElo "2400"]
+[BlackElo "?"]
+[Result "1-0"]
+
+1. e4 e5 2. Nf3 {attacks e5 (and more)} Nc6 (2... d6 3. d4 {Philidor} (3. Bc4)) 3. Bb5 $1 a6
+; the Morphy defence
+4.Ba4 Nf6 5. O-O 1-0
+
+[FEN "4k3/8/8/8/8/8/4P3/4K3 w - - 0 1"]
+
+1. e4 Kd7 *
+1. d4 d5 1/2-1/2
+"#;
+        let games = parse_pgn(contents).unwrap();
+        assert_eq!(games.len(), 3);
+        assert_eq!(games[0].tag("Event"), Some("Casual \"blitz\""));
+        assert_eq!((games[0].white_elo(), games[0].black_elo()), (Some(2400), None));
+        assert_eq!(games[0].moves, vec!["e4", "e5", "Nf3", "Nc6", "Bb5", "a6", "Ba4", "Nf6", "O-O"]);
+        assert_eq!(games[0].result, Some(GameResult::WhiteWins));
+        assert_eq!(games[1].result, None);
+        assert_eq!((games[2].tags.len(), games[2].result), (0, Some(GameResult::Draw)));
+
+        // replayed through the rules
+        let record = games[0].to_record().unwrap();
+        assert_eq!(record.moves[8], (4, 6));
+        assert_eq!(record.replay().unwrap().to_fen(), "r1bqkb1r/1ppp1ppp/p1n2n2/4p3/B3P3/5N2/PPPP1PPP/RNBQ1RK1 b kq - 3 5");
+        assert_eq!(games[1].to_record().unwrap().moves, vec![(12, 28), (60, 51)]);
+
+        assert_eq!(parse_pgn("[Event \"x\"").unwrap_err(), PgnError::InvalidTag);
+        assert_eq!(parse_pgn("[Event x]").unwrap_err(), PgnError::InvalidTag);
+        assert_eq!(parse_pgn("1. e4 {open").unwrap_err(), PgnError::UnterminatedComment);
+        assert_eq!(parse_pgn("1. e4 (1. d4").unwrap_err(), PgnError::UnterminatedVariation);
+        let illegal = parse_pgn("1. e4 e4 *").unwrap();
+        assert_eq!(illegal[0].to_record().unwrap_err(), PgnError::InvalidMove(SanError::IllegalMove));
+        let position = parse_pgn("[FEN \"8/8 w\"] *").unwrap();
+        assert_eq!(position[0].to_record().unwrap_err(), PgnError::InvalidPosition(FenError::WrongFieldCount));
+    }
+
+    #[test]
+    fn test_book_builder() {
+        println!("\n*******************\nRunning test_book_builder()\n*******************\n");
+
+        let contents = r#"
+[WhiteElo "2400"] [BlackElo "2300"] 1. e4 e5 2. Nf3 1-0
+[WhiteElo "2400"] [BlackElo "2300"] 1. e4 e5 2. Nf3 1/2-1/2
+[WhiteElo "1800"] [BlackElo "2300"] 1. e4 c5 2. Nf3 0-1
+[WhiteElo "2400"] [BlackElo "2300"] 1. d4 d5 0-1
+[WhiteElo "2400"] [BlackElo "2300"] 1. e4 e4 1-0
+"#;
+        let start = Chessboard::new();
+        let mut after_e4 = start.clone();
+        after_e4.perform_move("e2", "e4", PlayerColor::White).unwrap();
+
+        // every move counts: won, drawn and lost games weigh 2, 1 and 0 for the side that moved
+        let mut builder = BookBuilder::new(BookFilters {
+            min_occurrences: 1,
+            ..BookFilters::default()
+        });
+        builder.add_pgn(contents).unwrap();
+        assert_eq!((builder.games_added, builder.games_skipped), (4, 1));
+        let book = builder.build();
+        assert_eq!(book.moves(&start), vec![((12, 28), 3), ((11, 27), 0)]);
+        assert_eq!(book.moves(&after_e4), vec![((50, 34), 2), ((52, 36), 1)]);
+
+        // the weak player's e4 is left out, and so are the moves played only once
+        let mut builder = BookBuilder::new(BookFilters {
+            min_rating: 2000,
+            max_ply: 40,
+            min_occurrences: 2,
+        });
+        builder.add_pgn(contents).unwrap();
+        let book = builder.build();
+        assert_eq!(book.moves(&start), vec![((12, 28), 3)]);
+        assert_eq!(book.moves(&after_e4), vec![((52, 36), 1)]);
+
+        // only the first ply of each game
+        let mut builder = BookBuilder::new(BookFilters {
+            min_rating: 0,
+            max_ply: 1,
+            min_occurrences: 1,
+        });
+        builder.add_pgn(contents).unwrap();
+        let book = builder.build();
+        assert_eq!(book.entries().len(), 2);
+        assert_eq!(book.moves(&after_e4), vec![]);
+
+        // written and read back as a Polyglot file
+        let path = std::env::temp_dir().join("engine_test_built_book.bin");
+        book.save(path.to_str().unwrap()).unwrap();
+        assert_eq!(OpeningBook::open(path.to_str().unwrap()).unwrap(), book);
+        assert_eq!(book.save("no/such/directory/book.bin").unwrap_err(), BookError::WriteFailed);
+    }
+
+    #[test]
+    fn test_draws_and_contempt() {
+        println!("\n*******************\nRunning test_draws_and_contempt()\n*******************\n");
+
+        let search_with = |fen: &str, limits: &SearchLimits, contempt: i64| {
+            let options = SearchOptions {
+                contempt,
+                ..SearchOptions::deterministic()
+            };
+            search(
+                &Chessboard::from_fen(fen).unwrap(),
+                limits,
+                &options,
+                &TranspositionTable::new(1),
+                &MoveOrderer::new(),
+                &StopFlag::new(),
+                None
+            )
+        };
+
+        // a queen up, but every move ends the fifty moves
+        let limits = SearchLimits::depth(3);
+        assert!(search_with("4k3/8/8/8/8/8/8/Q3K3 w - - 0 80", &limits, 0).score > Score::centipawns(QUEEN_VALUE / 2));
+        assert_eq!(search_with("4k3/8/8/8/8/8/8/Q3K3 w - - 99 80", &limits, 0).score, Score::DRAW);
+
+        // contempt makes the draw cost the side to move at the root, whichever it is
+        assert_eq!(search_with("4k3/8/8/8/8/8/8/Q3K3 w - - 99 80", &limits, 50).score, Score::centipawns(-50));
+        assert_eq!(search_with("q3k3/8/8/8/8/8/8/4K3 b - - 99 80", &limits, 50).score, Score::centipawns(-50));
+        assert_eq!(search_with("q3k3/8/8/8/8/8/8/4K3 b - - 99 80", &limits, -50).score, Score::centipawns(50));
+
+        // a queen down, going back to a position of the game draws by repetition
+        let repeated = Chessboard::from_fen("q3k3/8/8/8/8/8/8/3K4 b - - 11 80").unwrap().zobrist_hash();
+        let limits = SearchLimits {
+            game_history: vec![repeated, 1, 2],
+            ..SearchLimits::depth(3)
+        };
+        let result = search_with("q3k3/8/8/8/8/8/8/4K3 w - - 10 80", &limits, 0);
+        assert_eq!((result.best_move, result.score), ((4, 3), Score::DRAW));
+        let result = search_with("q3k3/8/8/8/8/8/8/4K3 w - - 10 80", &limits, 50);
+        assert_eq!((result.best_move, result.score), ((4, 3), Score::centipawns(-50)));
+        // not once a capture or pawn move came in between
+        let result = search_with("q3k3/8/8/8/8/8/8/4K3 w - - 2 80", &limits, 0);
+        assert!(result.score < Score::centipawns(-QUEEN_VALUE / 2));
+
+        // a pawn down, stalemating the black king is the only way to stop the pawn
+        let result = search_with("8/8/8/8/8/5K2/7p/7k w - - 0 80", &limits, 0);
+        assert_eq!((result.best_move, result.score), ((21, 13), Score::DRAW));
+        let result = search_with("8/8/8/8/8/5K2/7p/7k w - - 0 80", &limits, 50);
+        assert_eq!((result.best_move, result.score), ((21, 13), Score::centipawns(-50)));
+
+        let mut options = SearchOptions::default();
+        options.set_option("Contempt", "-20").unwrap();
+        assert_eq!(options.contempt, -20);
+        assert_eq!(options.set_option("Contempt", "much"), Err(OptionError::InvalidValue));
+    }
+
+    #[test]
+    fn test_skill_level() {
+        println!("\n*******************\nRunning test_skill_level()\n*******************\n");
+
+        assert_eq!(level_from_elo(0), 0);
+        assert_eq!(level_from_elo(SKILL_MIN_ELO), 0);
+        assert_eq!(level_from_elo(SKILL_MAX_ELO), MAX_SKILL_LEVEL - 1);
+        assert_eq!(level_from_elo(SKILL_MAX_ELO + 1), MAX_SKILL_LEVEL);
+        assert!(level_from_elo(1500) > level_from_elo(1200));
+
+        // the level's limits, unless the caller's are tighter
+        let limits = limit_search(&SearchLimits::default(), 0);
+        assert_eq!((limits.depth, limits.nodes), (Some(1), Some(1_000)));
+        let limits = limit_search(&SearchLimits::depth(2), 10);
+        assert_eq!((limits.depth, limits.nodes), (Some(2), Some(32_000)));
+        let limits = limit_search(&SearchLimits::move_time(Duration::from_millis(20)), 10);
+        assert_eq!((limits.depth, limits.move_time), (Some(6), Some(Duration::from_millis(20))));
+        let limits = limit_search(&SearchLimits::infinite(), 10);
+        assert_eq!((limits.depth, limits.infinite), (Some(6), false));
+
+        // close lines are picked at random, a much worse one only by the weakest levels
+        let line = |score, next_move| SearchLine {
+            score: Score::centipawns(score),
+            principal_variation: vec![next_move],
+        };
+        let close = [line(50, (12, 28)), line(45, (11, 27)), line(40, (6, 21))];
+        let picked: Vec<_> = (0..50).map(|random| pick_line(&close, 10, random).principal_variation[0]).collect();
+        assert!(close.iter().all(|line| picked.contains(&line.principal_variation[0])));
+        let blunder = [line(50, (12, 28)), line(-400, (11, 27))];
+        assert!((0..50).all(|random| pick_line(&blunder, 19, random).principal_variation[0] == (12, 28)));
+
+        // with a seed the same position gets the same move every time
+        let search_at = |level: usize, seed: u64| {
+            let options = SearchOptions {
+                skill_level: level,
+                seed: Some(seed),
+                ..SearchOptions::deterministic()
+            };
+            search_with_skill(
+                &Chessboard::new(),
+                &SearchLimits::default(),
+                &options,
+                &TranspositionTable::new(1),
+                &MoveOrderer::new(),
+                &StopFlag::new(),
+                None
+            )
+        };
+        let weak = search_at(0, 42);
+        assert_eq!((weak.depth, weak.lines.len()), (1, 4));
+        assert!(weak.lines.iter().any(|line| line.principal_variation == weak.principal_variation));
+        assert_eq!(search_at(0, 42).best_move, weak.best_move);
+        let moves: Vec<_> = (0..20).map(|seed| search_at(0, seed).best_move).collect();
+        assert!(moves.iter().any(|next_move| *next_move != moves[0]));
+
+        // even with more threads and a clock the level's limits run out before
+        let options = SearchOptions {
+            skill_level: 6,
+            seed: Some(7),
+            threads: 4,
+            ..SearchOptions::default()
+        };
+        let limits = SearchLimits::clock(Duration::from_secs(60), Duration::from_secs(60), Duration::ZERO, Duration::ZERO, None);
+        let limited = limit_search(&limits, 6);
+        assert_eq!((limited.white_time, limited.depth, limited.nodes), (Some(Duration::from_secs(60)), Some(4), Some(8_000)));
+        let chessboard = Chessboard::from_fen("r1bqk1nr/pppp1ppp/2n5/2b1p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 4 4").unwrap();
+        let search_once = || search_with_skill(
+            &chessboard,
+            &limits,
+            &options,
+            &TranspositionTable::new(1),
+            &MoveOrderer::new(),
+            &StopFlag::new(),
+            None
+        );
+        let first = search_once();
+        assert!((0..3).all(|_| search_once().best_move == first.best_move));
+
+        // the top level is the plain search
+        let full = search_at(MAX_SKILL_LEVEL, 42);
+        assert_eq!((full.depth, full.lines.len()), (5, 1));
+
+        let mut options = SearchOptions::default();
+        options.set_option("SkillLevel", "3").unwrap();
+        assert_eq!(options.skill_level, 3);
+        options.set_option("Elo", "3000").unwrap();
+        assert_eq!(options.skill_level, MAX_SKILL_LEVEL);
+        options.set_option("Seed", "42").unwrap();
+        assert_eq!(options.seed, Some(42));
+        options.set_option("Seed", "").unwrap();
+        assert_eq!(options.seed, None);
+        assert_eq!(options.set_option("SkillLevel", "21"), Err(OptionError::InvalidValue));
+        assert_eq!(options.set_option("Elo", "strong"), Err(OptionError::InvalidValue));
     }
 }